@@ -0,0 +1,46 @@
+// Compile the syntax definitions cath ships with into a dump for src/assets.rs to embed,
+// and generate include/cath.h, the C header for the bindings the ffi feature adds
+use std::env;
+use std::path::Path;
+
+use syntect::dumps::dump_to_file;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+
+// Syntax definitions that come with cath, for formats syntect's defaults don't cover
+const BUNDLED_SYNTAXES: [&str; 1] = ["src/syntaxes/Manpage.sublime-syntax"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    build_syntaxes();
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        cbindgen::Builder::new()
+            .with_src("src/ffi.rs")
+            .with_language(cbindgen::Language::C)
+            .with_include_guard("CATH_H")
+            .with_header("/* Generated from src/ffi.rs by build.rs; don't edit */")
+            .generate()
+            .expect("Failed to generate the C header")
+            .write_to_file("include/cath.h");
+    }
+}
+
+// Link syntect's default syntaxes together with the bundled ones ahead of time. Loading
+// the result only reads each syntax's name and file types up front, and the rest of a
+// syntax the first time it's used, where building the set at startup would go through
+// all of them
+fn build_syntaxes() {
+    let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+    for path in BUNDLED_SYNTAXES {
+        println!("cargo:rerun-if-changed={}", path);
+        let source = std::fs::read_to_string(path).expect("Failed to read a bundled syntax");
+        builder.add(
+            SyntaxDefinition::load_from_str(&source, true, None)
+                .expect("a bundled syntax definition is broken"),
+        );
+    }
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    dump_to_file(&builder.build(), Path::new(&out_dir).join("syntaxes.bin"))
+        .expect("Failed to write the syntax dump");
+}
@@ -0,0 +1,52 @@
+/* Generated from src/ffi.rs by build.rs; don't edit */
+
+#ifndef CATH_H
+#define CATH_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Highlight `code` with 24-bit terminal color escapes.
+ *
+ * `language` is a syntax name or file extension (e.g. "Rust" or "rs"), or NULL to detect
+ * it from the code. `theme` is the name of a color theme, or NULL for the default one.
+ * Returns a string to free with cath_free_string, or NULL on error (see cath_last_error).
+ *
+ * # Safety
+ *
+ * `code` must be a NUL-terminated UTF-8 string, and `language` and `theme` either NULL or
+ * NUL-terminated UTF-8 strings.
+ */
+char *cath_render_ansi(const char *code, const char *language, const char *theme);
+
+/**
+ * Highlight `code` as an HTML <pre> block with inline styles.
+ *
+ * Takes the same arguments, and returns the same way, as cath_render_ansi.
+ *
+ * # Safety
+ *
+ * The same as for cath_render_ansi.
+ */
+char *cath_render_html(const char *code, const char *language, const char *theme);
+
+/**
+ * Free a string returned by cath_render_ansi or cath_render_html. NULL is ignored.
+ *
+ * # Safety
+ *
+ * `string` must have come from one of those functions, and not been freed already.
+ */
+void cath_free_string(char *string);
+
+/**
+ * The message for the last error on this thread, or NULL when there hasn't been one.
+ *
+ * The string belongs to the library and stays valid until the next call on this thread.
+ */
+const char *cath_last_error(void);
+
+#endif  /* CATH_H */
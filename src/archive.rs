@@ -0,0 +1,229 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use zip::ExtraField;
+
+use crate::compression::Compression;
+use crate::printer;
+
+// The archive formats that members can be read out of
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zip,
+    // A tarball, which may itself be compressed in any format that's compiled in
+    Tar,
+}
+
+// Extensions of archive files, checked against the end of the file name so that two-part
+// ones like .tar.gz work
+const EXTENSIONS: &[(&str, Format)] = &[
+    (".zip", Format::Zip),
+    (".jar", Format::Zip),
+    (".tar", Format::Tar),
+    (".tar.gz", Format::Tar),
+    (".tgz", Format::Tar),
+    (".tar.bz2", Format::Tar),
+    (".tbz2", Format::Tar),
+    (".tar.xz", Format::Tar),
+    (".txz", Format::Tar),
+    (".tar.zst", Format::Tar),
+];
+
+// The archive format of the file at `path`, judging by its name
+fn format(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| name.ends_with(ext))
+        .map(|&(_, format)| format)
+}
+
+// Split an "archive.zip:path/inside" argument into the archive and the member's path, or
+// the archive alone (with no member) when `path` names an archive file itself. None for
+// anything else, including files that merely have a colon in their name
+pub fn split(path: &Path) -> Option<(PathBuf, Option<String>)> {
+    if path.is_file() {
+        return format(path).map(|_| (path.to_path_buf(), None));
+    }
+    let text = path.to_str()?;
+    // The member's path can contain colons too, so the archive ends at the first colon
+    // that leaves an existing archive before it
+    text.match_indices(':').find_map(|(index, _)| {
+        let archive = Path::new(&text[..index]);
+        (format(archive).is_some() && archive.is_file())
+            .then(|| (archive.to_path_buf(), Some(text[index + 1..].to_string())))
+    })
+}
+
+// A member of an archive, as shown in its listing
+pub struct Entry {
+    pub name: String,
+    // The uncompressed size in bytes
+    pub size: u64,
+    // When the member was last modified, formatted the same for zip and tar archives
+    pub modified: Option<String>,
+    pub is_dir: bool,
+}
+
+// Read the data of a member of an archive
+pub fn read_member(archive: &Path, member: &str) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match archive_format(archive)? {
+        Format::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+            let mut entry = zip.by_name(member).map_err(|_| not_found(member))?;
+            entry.read_to_end(&mut data)?;
+        }
+        Format::Tar => {
+            let mut tar = open_tar(archive)?;
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                if same_member(&entry.path()?, member) {
+                    entry.read_to_end(&mut data)?;
+                    return Ok(data);
+                }
+            }
+            return Err(not_found(member));
+        }
+    }
+    Ok(data)
+}
+
+// List the members of an archive, in the order they're stored in
+pub fn entries(archive: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    match archive_format(archive)? {
+        Format::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::other)?;
+            for index in 0..zip.len() {
+                // The raw entry is enough for the metadata, and skips decompressing it
+                let entry = zip.by_index_raw(index).map_err(io::Error::other)?;
+                entries.push(Entry {
+                    name: entry.name().to_string(),
+                    size: entry.size(),
+                    modified: zip_mtime(&entry).map(printer::format_time),
+                    is_dir: entry.is_dir(),
+                });
+            }
+        }
+        Format::Tar => {
+            let mut tar = open_tar(archive)?;
+            for entry in tar.entries()? {
+                let entry = entry?;
+                let header = entry.header();
+                entries.push(Entry {
+                    name: entry.path()?.to_string_lossy().into_owned(),
+                    size: header.size()?,
+                    modified: header
+                        .mtime()
+                        .ok()
+                        .map(|mtime| printer::format_time(mtime as i64)),
+                    is_dir: header.entry_type().is_dir(),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// The Unix timestamp of when a zip member was last modified: from its extended timestamp
+// field, which is in UTC, when it has one. The time zip keeps itself has no time zone
+// (it's local to wherever the archive was made), and is shown as if it were UTC, so the
+// listing reads like a tarball's
+fn zip_mtime(entry: &zip::read::ZipFile) -> Option<i64> {
+    let extended = entry.extra_data_fields().find_map(|field| match field {
+        ExtraField::ExtendedTimestamp(timestamp) => timestamp.mod_time(),
+        _ => None,
+    });
+    if let Some(mtime) = extended {
+        return Some(i64::from(mtime));
+    }
+    let time = entry.last_modified()?;
+    let days = days_from_civil(
+        i64::from(time.year()),
+        i64::from(time.month()),
+        i64::from(time.day()),
+    );
+    Some(
+        days * 86_400
+            + i64::from(time.hour()) * 3_600
+            + i64::from(time.minute()) * 60
+            + i64::from(time.second()),
+    )
+}
+
+// The days since the Unix epoch of a civil date, the inverse of git::format_date's
+// conversion (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Write the listing of an archive's members, a line each with the size, the modification
+// time and the name, colored like ls colors them when `colored` is set
+pub fn write_listing(handle: &mut dyn Write, entries: &[Entry], colored: bool) -> io::Result<()> {
+    let color = |escape: &'static str| if colored { escape } else { "" };
+    let reset = color("\x1b[0m");
+    let modified_width = entries
+        .iter()
+        .filter_map(|entry| entry.modified.as_ref())
+        .map(String::len)
+        .max()
+        .unwrap_or(0);
+    for entry in entries {
+        let size = if entry.is_dir {
+            "-".to_string()
+        } else {
+            printer::format_size(entry.size)
+        };
+        let modified = entry.modified.as_deref().unwrap_or("-");
+        let name_color = color(if entry.is_dir { "\x1b[1;34m" } else { "" });
+        writeln!(
+            handle,
+            "{}{:>10}{}  {}{:modified_width$}{}  {}{}{}",
+            color("\x1b[32m"),
+            size,
+            reset,
+            color("\x1b[34m"),
+            modified,
+            reset,
+            name_color,
+            entry.name,
+            reset
+        )?;
+    }
+    Ok(())
+}
+
+// The archive format of `archive`, which split has already checked it has
+fn archive_format(archive: &Path) -> io::Result<Format> {
+    format(archive).ok_or_else(|| io::Error::other("not an archive"))
+}
+
+// Open a tarball for reading its entries, decompressing it first when it's compressed
+fn open_tar(archive: &Path) -> io::Result<tar::Archive<Box<dyn BufRead>>> {
+    let mut reader: Box<dyn BufRead> = Box::new(BufReader::new(File::open(archive)?));
+    if let Some(compression) = Compression::detect(reader.fill_buf()?) {
+        reader = compression.decoder(reader)?;
+    }
+    Ok(tar::Archive::new(reader))
+}
+
+// Whether a tar entry's path is the member asked for; tarballs made with "tar -C dir ."
+// put a "./" in front of every path
+fn same_member(path: &Path, member: &str) -> bool {
+    let member = Path::new(member);
+    path == member || path.strip_prefix(".").is_ok_and(|path| path == member)
+}
+
+fn not_found(member: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no member named '{}' in the archive", member),
+    )
+}
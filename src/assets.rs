@@ -0,0 +1,180 @@
+use std::cell::OnceCell;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use syntect::dumps::{dump_to_uncompressed_file, from_binary, from_uncompressed_dump_file};
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
+
+// File names inside the cache directory
+const SYNTAX_CACHE_FILE: &str = "syntaxes.bin";
+const THEME_CACHE_FILE: &str = "themes.bin";
+// Records which cath version wrote the cache, since dumps aren't portable across versions
+const METADATA_CACHE_FILE: &str = "metadata.txt";
+
+// The syntax definitions and color themes used for highlighting, combining
+// syntect's built-in defaults with anything the user dropped into their config directory
+// Syntax definitions are by far the bigger part, so they're only loaded the first time
+// they're needed. syntect links syntaxes to each other (e.g. Markdown embeds every fenced
+// language), so the set is loaded as a whole, but that only reads each syntax's name and
+// file types: the rest of a syntax is deserialized the first time it's used
+pub struct HighlightingAssets {
+    syntax_set: OnceCell<SyntaxSet>,
+    pub theme_set: ThemeSet,
+}
+
+impl HighlightingAssets {
+    // Load the themes, from the binary cache when one has been built, otherwise from the
+    // defaults merged with user customizations
+    pub fn load() -> Self {
+        let theme_set = cache_file(THEME_CACHE_FILE)
+            .and_then(|path| from_uncompressed_dump_file(path).ok())
+            .unwrap_or_else(themes_from_sources);
+        HighlightingAssets {
+            syntax_set: OnceCell::new(),
+            theme_set,
+        }
+    }
+
+    // The full syntax set, loaded (from the cache or from sources) on first use
+    pub fn syntax_set(&self) -> &SyntaxSet {
+        self.syntax_set.get_or_init(|| {
+            cache_file(SYNTAX_CACHE_FILE)
+                .and_then(|path| from_uncompressed_dump_file(path).ok())
+                .unwrap_or_else(syntaxes_from_sources)
+        })
+    }
+}
+
+// A set holding only the plain text syntax, which is all that output without highlighting
+// needs and costs next to nothing to build
+pub fn plain_text_syntax_set() -> SyntaxSet {
+    let mut builder = SyntaxSetBuilder::new();
+    builder.add_plain_text_syntax();
+    builder.build()
+}
+
+// syntect's default syntax definitions (Rust, Python, JavaScript, etc.) and the ones
+// cath bundles, linked together by build.rs
+const BUILT_IN_SYNTAXES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/syntaxes.bin"));
+
+// Load the built-in syntax definitions, and merge in user customizations. Only the user's
+// own definitions make the set need linking again; without any, each syntax is only
+// deserialized once something is highlighted with it
+fn syntaxes_from_sources() -> SyntaxSet {
+    let built_in: SyntaxSet = from_binary(BUILT_IN_SYNTAXES);
+    let Some(dir) = user_syntaxes_dir() else {
+        return built_in;
+    };
+    let mut builder = built_in.into_builder();
+    // As with themes, a broken definition is reported but doesn't stop cath from running
+    if let Err(err) = builder.add_from_folder(&dir, true) {
+        eprintln!(
+            "cath: warning: failed to load syntaxes from {}: {}",
+            dir.display(),
+            err
+        );
+    }
+    builder.build()
+}
+
+// Load the default color themes (includes various dark/light themes) and merge in user
+// customizations
+fn themes_from_sources() -> ThemeSet {
+    let mut theme_set = ThemeSet::load_defaults();
+    add_user_themes(&mut theme_set);
+    theme_set
+}
+
+// Path of one serialized asset in the cache, or None if there is no usable cache
+fn cache_file(file: &str) -> Option<PathBuf> {
+    let dir = cache_dir()?;
+    // Ignore caches written by a different version of cath
+    let version = fs::read_to_string(dir.join(METADATA_CACHE_FILE)).ok()?;
+    if version.trim() != env!("CARGO_PKG_VERSION") {
+        return None;
+    }
+    Some(dir.join(file))
+}
+
+// Compile the defaults plus user themes and syntaxes and serialize them into the cache directory
+// Returns the directory that was written
+pub fn build_cache() -> io::Result<PathBuf> {
+    let dir = cache_dir().ok_or_else(|| io::Error::other("could not determine cache directory"))?;
+    fs::create_dir_all(&dir)?;
+
+    dump_to_uncompressed_file(&syntaxes_from_sources(), dir.join(SYNTAX_CACHE_FILE))
+        .map_err(io::Error::other)?;
+    dump_to_uncompressed_file(&themes_from_sources(), dir.join(THEME_CACHE_FILE))
+        .map_err(io::Error::other)?;
+    // Write the metadata last so a partially written cache is never picked up
+    fs::write(dir.join(METADATA_CACHE_FILE), env!("CARGO_PKG_VERSION"))?;
+    Ok(dir)
+}
+
+// Remove the cache files so cath goes back to loading assets from their sources
+// Returns the directory that was cleared
+pub fn clear_cache() -> io::Result<PathBuf> {
+    let dir = cache_dir().ok_or_else(|| io::Error::other("could not determine cache directory"))?;
+    for file in [METADATA_CACHE_FILE, SYNTAX_CACHE_FILE, THEME_CACHE_FILE] {
+        match fs::remove_file(dir.join(file)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    Ok(dir)
+}
+
+// Directory holding cath's asset cache: $XDG_CACHE_HOME/cath, falling back to ~/.cache/cath
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cache")))?;
+    Some(base.join("cath"))
+}
+
+// Directory holding cath's user configuration: $XDG_CONFIG_HOME/cath, falling back to ~/.config/cath
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))?;
+    Some(base.join("cath"))
+}
+
+// The current user's home directory
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// Merge every .tmTheme file found in the user themes directory into `theme_set`
+// Each theme is named after its file stem, so "~/.config/cath/themes/Dracula.tmTheme" becomes "Dracula"
+fn add_user_themes(theme_set: &mut ThemeSet) {
+    let Some(dir) = config_dir().map(|dir| dir.join("themes")) else {
+        return;
+    };
+    if !dir.is_dir() {
+        return;
+    }
+    // A broken theme file shouldn't prevent cath from running, so only warn about it
+    if let Err(err) = theme_set.add_from_folder(&dir) {
+        eprintln!(
+            "cath: warning: failed to load themes from {}: {}",
+            dir.display(),
+            err
+        );
+    }
+}
+
+// The user syntaxes directory, when there is one
+fn user_syntaxes_dir() -> Option<PathBuf> {
+    config_dir()
+        .map(|dir| dir.join("syntaxes"))
+        .filter(|dir| dir.is_dir())
+}
@@ -0,0 +1,76 @@
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+
+use crate::terminal::AnsiFilter;
+
+// What --copy puts on the clipboard
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CopyFormat {
+    // The output as it was rendered, colors included
+    Ansi,
+    // The output with all escape sequences removed, for pasting into places that don't
+    // understand them
+    Plain,
+}
+
+// A writer that keeps a copy of everything passed through it when a format is set, for
+// putting on the clipboard with copy() after the last of the output
+pub struct ClipboardCopy<W: Write> {
+    inner: W,
+    copy: Option<(CopyFormat, Vec<u8>)>,
+}
+
+impl<W: Write> ClipboardCopy<W> {
+    pub fn new(inner: W, format: Option<CopyFormat>) -> Self {
+        ClipboardCopy {
+            inner,
+            copy: format.map(|format| (format, Vec::new())),
+        }
+    }
+
+    // Put what was written on the clipboard, once; without a format there's nothing to do
+    pub fn copy(&mut self) -> io::Result<()> {
+        match self.copy.take() {
+            Some((format, copy)) => copy_to_clipboard(&copy, format),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Write for ClipboardCopy<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some((_, copy)) = &mut self.copy {
+            copy.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Put `text` on the clipboard with an OSC 52 escape sequence, which the terminal acts on
+// (also over ssh, unlike a local clipboard library). It's written to the controlling
+// terminal, since stdout may be a pipe or a file
+fn copy_to_clipboard(text: &[u8], format: CopyFormat) -> io::Result<()> {
+    let text = match format {
+        CopyFormat::Ansi => text.to_vec(),
+        CopyFormat::Plain => {
+            let mut plain = Vec::with_capacity(text.len());
+            AnsiFilter::new(&mut plain, true).write_all(text)?;
+            plain
+        }
+    };
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    match OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut tty) => tty.write_all(sequence.as_bytes()),
+        Err(_) if io::stderr().is_terminal() => io::stderr().write_all(sequence.as_bytes()),
+        Err(err) => Err(err),
+    }
+}
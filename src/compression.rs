@@ -0,0 +1,87 @@
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use flate2::bufread::MultiGzDecoder;
+
+// A compression format that inputs are transparently decompressed from. Formats other than
+// gzip need their cargo feature, since they bring in C libraries
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    #[cfg(feature = "xz")]
+    Xz,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    // The format `block` (the start of an input) is compressed in, judging by its magic
+    // bytes, or None for uncompressed data (and formats that aren't compiled in)
+    pub fn detect(block: &[u8]) -> Option<Self> {
+        if block.starts_with(b"\x1f\x8b") {
+            return Some(Compression::Gzip);
+        }
+        // "BZh" and the block size are followed by the magic number for a compressed block
+        // (or an empty stream's end), which keeps text that starts with "BZh" from passing
+        #[cfg(feature = "bzip2")]
+        if block.len() >= 10
+            && block.starts_with(b"BZh")
+            && (b'1'..=b'9').contains(&block[3])
+            && (block[4..10] == *b"\x31\x41\x59\x26\x53\x59"
+                || block[4..10] == *b"\x17\x72\x45\x38\x50\x90")
+        {
+            return Some(Compression::Bzip2);
+        }
+        #[cfg(feature = "xz")]
+        if block.starts_with(b"\xfd7zXZ\0") {
+            return Some(Compression::Xz);
+        }
+        #[cfg(feature = "zstd")]
+        if block.starts_with(b"\x28\xb5\x2f\xfd") {
+            return Some(Compression::Zstd);
+        }
+        None
+    }
+
+    // Wrap `reader` so it yields the decompressed data. Multi-stream files (e.g.
+    // concatenated gzip files, or a parallel bzip2's output) are read in full
+    pub fn decoder(self, reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => {
+                Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader)))
+            }
+            #[cfg(feature = "xz")]
+            Compression::Xz => Box::new(BufReader::new(
+                xz2::bufread::XzDecoder::new_multi_decoder(reader),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(BufReader::new(
+                zstd::stream::read::Decoder::with_buffer(reader)?,
+            )),
+        })
+    }
+}
+
+// Extensions of compressed files, for the formats that are compiled in
+const EXTENSIONS: &[&str] = &[
+    "gz",
+    #[cfg(feature = "bzip2")]
+    "bz2",
+    #[cfg(feature = "xz")]
+    "xz",
+    #[cfg(feature = "zstd")]
+    "zst",
+];
+
+// The path to detect the syntax of a file by: a compressed file goes by the name of what's
+// inside, e.g. app.log for app.log.gz
+pub fn content_path(path: &Path) -> PathBuf {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if EXTENSIONS.contains(&ext) => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
+}
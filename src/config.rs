@@ -0,0 +1,239 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::assets;
+
+// Location of the config file when --config-file isn't given: ~/.config/cath/config
+pub fn default_config_path() -> Option<PathBuf> {
+    assets::config_dir().map(|dir| dir.join("config"))
+}
+
+// Environment variables that stand in for a single command-line option
+const ENV_OPTIONS: &[(&str, &str)] = &[
+    ("CATH_THEME", "--theme"),
+    ("CATH_THEME_DARK", "--theme-dark"),
+    ("CATH_THEME_LIGHT", "--theme-light"),
+    ("CATH_STYLE", "--style"),
+    ("CATH_HYPERLINK_FORMAT", "--hyperlink-format"),
+];
+
+// Build the full argument list: the program name, then the options from the config file,
+// then the options from the environment, then the real command-line arguments. Since later
+// occurrences of an option win, the command line overrides the environment, which in turn
+// overrides the config file
+// `subcommands` lists the subcommand names, which must stay first and never get config options
+pub fn merged_args(subcommands: &[&str]) -> Vec<OsString> {
+    let mut cli: Vec<OsString> = env::args_os().collect();
+    let program = if cli.is_empty() {
+        OsString::from("cath")
+    } else {
+        cli.remove(0)
+    };
+
+    let mut merged = vec![program];
+    let runs_subcommand = cli
+        .first()
+        .and_then(|arg| arg.to_str())
+        .is_some_and(|arg| subcommands.contains(&arg));
+    if !runs_subcommand {
+        merged.extend(config_args(&cli));
+        merged.extend(env_args());
+    }
+    merged.extend(cli);
+    merged
+}
+
+// Read the options stored in the config file, exiting with an error if it can't be read.
+// The default config file is optional, so only a missing one is silently ignored; one that
+// is there but broken is reported the same as one given with --config-file
+fn config_args(cli: &[OsString]) -> Vec<OsString> {
+    let (path, required) = match config_file_override(cli) {
+        Some(path) => (path, true),
+        None => match default_config_path() {
+            Some(path) => (path, false),
+            None => return Vec::new(),
+        },
+    };
+    match read_config_file(&path) {
+        Ok(args) => args,
+        Err(err) if !required && err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            eprintln!(
+                "cath: failed to read config file {}: {}",
+                path.display(),
+                err
+            );
+            process::exit(1);
+        }
+    }
+}
+
+// Collect options from the environment: everything in CATH_OPTS (split like a config file
+// line), followed by the single-option variables such as CATH_THEME
+fn env_args() -> Vec<OsString> {
+    let mut args = Vec::new();
+    if let Some(opts) = env::var("CATH_OPTS")
+        .ok()
+        .filter(|opts| !opts.trim().is_empty())
+    {
+        match shlex::split(&opts) {
+            Some(words) => args.extend(words.into_iter().map(OsString::from)),
+            None => {
+                eprintln!("cath: failed to parse CATH_OPTS: unbalanced quotes");
+                process::exit(1);
+            }
+        }
+    }
+    for (var, option) in ENV_OPTIONS {
+        if let Some(value) = env::var_os(var).filter(|value| !value.is_empty()) {
+            let mut arg = OsString::from(format!("{}=", option));
+            arg.push(value);
+            args.push(arg);
+        }
+    }
+    args
+}
+
+// Find a `--config-file PATH` or `--config-file=PATH` on the command line
+// This has to happen before clap runs, since the config file feeds into clap's input
+fn config_file_override(cli: &[OsString]) -> Option<PathBuf> {
+    let mut iter = cli.iter();
+    while let Some(arg) = iter.next() {
+        let arg = arg.to_str()?;
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config-file" {
+            return iter.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config-file=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+// Parse a config file: one or more command-line options per line, split with shell-style
+// quoting, with blank lines and lines starting with '#' ignored. For example:
+//
+//     # Use a light theme and always show line numbers
+//     --theme "Solarized (light)"
+//     --line-numbers
+//
+// A [preprocessors] section maps file globs to the commands whose output is shown for
+// them, one "GLOB -> COMMAND" per line (the same as a --preprocessor option), until an
+// [options] section goes back to options:
+//
+//     [preprocessors]
+//     *.plist -> plutil -p
+//     *.sqlite -> sqlite3 %s .dump
+pub fn read_config_file(path: &Path) -> io::Result<Vec<OsString>> {
+    let contents = fs::read_to_string(path)?;
+    let mut args = Vec::new();
+    let mut in_preprocessors = false;
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} on line {}", message, index + 1),
+            )
+        };
+        match line {
+            "[preprocessors]" => in_preprocessors = true,
+            "[options]" => in_preprocessors = false,
+            _ if line.starts_with('[') && line.ends_with(']') => {
+                return Err(invalid(&format!("unknown section {}", line)));
+            }
+            _ if in_preprocessors => {
+                let (pattern, command) = line
+                    .split_once("->")
+                    .ok_or_else(|| invalid("expected GLOB -> COMMAND"))?;
+                args.push(OsString::from(format!(
+                    "--preprocessor={}:{}",
+                    pattern.trim(),
+                    command.trim()
+                )));
+            }
+            _ => {
+                let words = shlex::split(line).ok_or_else(|| invalid("unbalanced quotes"))?;
+                args.extend(words.into_iter().map(OsString::from));
+            }
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parse `contents` as a config file, written to a file of its own under the temporary
+    // directory
+    fn parse(name: &str, contents: &str) -> io::Result<Vec<OsString>> {
+        let path = env::temp_dir().join(format!("cath-config-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        let parsed = read_config_file(&path);
+        let _ = fs::remove_file(&path);
+        parsed
+    }
+
+    #[test]
+    fn reads_options_and_preprocessors() {
+        let contents = "\
+# A comment
+--theme \"Solarized (light)\"
+
+--line-numbers --tabs=4
+[preprocessors]
+*.plist -> plutil -p
+[options]
+--plain
+";
+        let expected = [
+            "--theme",
+            "Solarized (light)",
+            "--line-numbers",
+            "--tabs=4",
+            "--preprocessor=*.plist:plutil -p",
+            "--plain",
+        ];
+        assert_eq!(
+            parse("valid", contents).unwrap(),
+            expected.map(OsString::from)
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        let cases = [
+            (
+                "quotes",
+                "--plain\n--theme \"x\n",
+                "unbalanced quotes on line 2",
+            ),
+            (
+                "section",
+                "[colors]\n",
+                "unknown section [colors] on line 1",
+            ),
+            (
+                "hook",
+                "[preprocessors]\n*.plist plutil\n",
+                "expected GLOB -> COMMAND on line 2",
+            ),
+        ];
+        for (name, contents, message) in cases {
+            let err = parse(name, contents).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(err.to_string(), message);
+        }
+    }
+}
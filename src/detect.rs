@@ -0,0 +1,386 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use glob::{MatchOptions, Pattern};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::compression;
+use crate::net;
+
+// Well-known file names that their extension (or lack of one) doesn't identify, with the
+// languages to try for them in order: the first is the best fit, which may only be there
+// as a custom syntax, and the others are close enough to fall back on
+static SPECIAL_FILE_NAMES: LazyLock<Vec<(Pattern, &[&str])>> = LazyLock::new(|| {
+    let names: &[(&str, &[&str])] = &[
+        ("Dockerfile", &["Dockerfile", "sh"]),
+        ("Dockerfile.*", &["Dockerfile", "sh"]),
+        ("*.dockerfile", &["Dockerfile", "sh"]),
+        ("Containerfile", &["Dockerfile", "sh"]),
+        ("CMakeLists.txt", &["CMake"]),
+        ("Makefile.*", &["Makefile"]),
+        ("Justfile", &["Just", "Makefile"]),
+        ("justfile", &["Just", "Makefile"]),
+        (".bashrc", &["sh"]),
+        (".bash_profile", &["sh"]),
+        (".bash_logout", &["sh"]),
+        (".profile", &["sh"]),
+        (".zshrc", &["sh"]),
+        (".zshenv", &["sh"]),
+        (".zprofile", &["sh"]),
+        (".kshrc", &["sh"]),
+        (".xinitrc", &["sh"]),
+        (".envrc", &["sh"]),
+        (".env", &["sh"]),
+        (".env.*", &["sh"]),
+        ("PKGBUILD", &["sh"]),
+        ("APKBUILD", &["sh"]),
+        ("Cargo.lock", &["TOML"]),
+        ("Pipfile", &["TOML"]),
+        ("poetry.lock", &["TOML"]),
+        ("Gemfile", &["Ruby"]),
+        ("Rakefile", &["Ruby"]),
+        ("Vagrantfile", &["Ruby"]),
+        ("Podfile", &["Ruby"]),
+        ("Jenkinsfile", &["Groovy"]),
+        ("SConstruct", &["Python"]),
+        ("SConscript", &["Python"]),
+        ("BUILD", &["Starlark", "Python"]),
+        ("BUILD.bazel", &["Starlark", "Python"]),
+        ("WORKSPACE", &["Starlark", "Python"]),
+        ("*.bzl", &["Starlark", "Python"]),
+        (".gitconfig", &["Git Config", "INI"]),
+        (".gitmodules", &["Git Config", "INI"]),
+        (".editorconfig", &["INI"]),
+        (".gitignore", &["Git Ignore"]),
+        (".babelrc", &["JSON"]),
+        (".eslintrc", &["JSON"]),
+        (".vimrc", &["VimL"]),
+        ("crontab", &["Crontab", "sh"]),
+    ];
+    names
+        .iter()
+        .map(|&(pattern, languages)| {
+            let pattern = Pattern::new(pattern).expect("built-in patterns are valid");
+            (pattern, languages)
+        })
+        .collect()
+});
+
+// A --map-syntax rule: files whose path matches `pattern` get the syntax for `language`
+#[derive(Clone, Debug)]
+pub struct SyntaxMapping {
+    pub pattern: Pattern,
+    pub language: String,
+}
+
+impl SyntaxMapping {
+    // Parse "GLOB:LANGUAGE", e.g. "*.conf:INI"; the language is looked up like --language
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (pattern, language) = value
+            .rsplit_once(':')
+            .filter(|(pattern, language)| !pattern.is_empty() && !language.trim().is_empty())
+            .ok_or_else(|| format!("expected GLOB:LANGUAGE, got '{}'", value))?;
+        Ok(SyntaxMapping {
+            pattern: parse_pattern(pattern)?,
+            language: language.trim().to_string(),
+        })
+    }
+}
+
+// Find the appropriate syntax definition for an input, given the start of its `content`
+// A modeline picks the syntax even over the extension, since it's there to correct it
+// Without a known extension (and always for stdin), the first line is looked at: a shebang
+// names the interpreter, some syntaxes recognize their own first lines (e.g. <?php), and
+// a few common formats are recognized by their content. The Content-Type of a fetched URL
+// counts for more than its content, but less than its path
+// If no syntax is found, fall back to plain text syntax
+pub fn detect_syntax<'a>(
+    ps: &'a SyntaxSet,
+    file_path: Option<&Path>,
+    content: &str,
+    content_type: Option<&str>,
+    mappings: &[SyntaxMapping],
+) -> &'a SyntaxReference {
+    // Compressed files go by the name of the file inside, and URLs by their path
+    let content_path = file_path.map(|path| compression::content_path(&net::content_path(path)));
+    find_syntax_by_modeline(ps, file_path, content)
+        .or_else(|| {
+            content_path
+                .as_deref()
+                .and_then(|path| find_syntax_by_path(ps, path, mappings))
+        })
+        .or_else(|| {
+            content_type.and_then(|content_type| net::find_syntax_by_content_type(ps, content_type))
+        })
+        .or_else(|| {
+            let first_line = LinesWithEndings::from(content).next()?;
+            find_syntax_by_shebang(ps, first_line)
+                .or_else(|| ps.find_syntax_by_first_line(first_line))
+                .or_else(|| find_syntax_by_content(ps, content))
+        })
+        .unwrap_or_else(|| ps.find_syntax_plain_text())
+}
+
+// Parse a shell-style wildcard pattern, as --map-syntax and --preprocessor take: "*" matches
+// any run of characters within a path component, "**" any number of components, "?" a
+// single character, and "[abc]", "[a-z]" or "[!abc]" one character from (or not from) a set
+pub fn parse_pattern(pattern: &str) -> Result<Pattern, String> {
+    Pattern::new(pattern).map_err(|err| format!("invalid pattern '{}': {}", pattern, err))
+}
+
+// Whether `path`, with forward slashes, matches `pattern`: its file name, for patterns
+// without a slash, or else the whole path. A pattern not starting with a slash may match
+// any trailing part of the path, so "src/*.rs" also matches "./src/main.rs" and
+// "/home/me/src/main.rs"
+pub fn path_matches(pattern: &Pattern, path: &str) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    if !pattern.as_str().contains('/') {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        return pattern.matches_with(name, options);
+    }
+    pattern.matches_with(path, options)
+        || (!pattern.as_str().starts_with('/')
+            && path
+                .match_indices('/')
+                .any(|(index, _)| pattern.matches_with(&path[index + 1..], options)))
+}
+
+// Look up a syntax from a file's name: the --map-syntax rules first (the last matching one
+// wins, so the command line overrides the config file), then the well-known file names
+// (e.g. "Dockerfile", or "CMakeLists.txt" which isn't plain text), then the extension,
+// then the whole file name (e.g. "Makefile")
+pub fn find_syntax_by_path<'a>(
+    ps: &'a SyntaxSet,
+    path: &Path,
+    mappings: &[SyntaxMapping],
+) -> Option<&'a SyntaxReference> {
+    let path_text = path.to_string_lossy().replace('\\', "/");
+    if let Some(mapping) = mappings
+        .iter()
+        .rev()
+        .find(|mapping| path_matches(&mapping.pattern, &path_text))
+    {
+        return find_syntax_by_language(ps, &mapping.language);
+    }
+    if let Some(syntax) = SPECIAL_FILE_NAMES
+        .iter()
+        .filter(|(pattern, _)| path_matches(pattern, &path_text))
+        .find_map(|(_, languages)| {
+            languages
+                .iter()
+                .find_map(|language| find_syntax_by_language(ps, language))
+        })
+    {
+        return Some(syntax);
+    }
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    extension
+        .and_then(|ext| ps.find_syntax_by_extension(ext))
+        .or_else(|| file_name.and_then(|name| ps.find_syntax_by_extension(name)))
+}
+
+// Look up a syntax from a user-supplied language: first by (case-insensitive) name, then by
+// extension, so both "Rust" and "rs" work
+pub fn find_syntax_by_language<'a>(
+    ps: &'a SyntaxSet,
+    language: &str,
+) -> Option<&'a SyntaxReference> {
+    ps.find_syntax_by_name(language)
+        .or_else(|| {
+            ps.syntaxes()
+                .iter()
+                .find(|syntax| syntax.name.eq_ignore_ascii_case(language))
+        })
+        .or_else(|| ps.find_syntax_by_extension(language))
+}
+
+// Look up a syntax from a shebang line like "#!/usr/bin/env python3" by the interpreter it
+// names. Interpreters are matched by name with any version number dropped ("python3.11"
+// is python), through a table for the ones whose name isn't also a syntax name or extension
+pub fn find_syntax_by_shebang<'a>(
+    ps: &'a SyntaxSet,
+    first_line: &str,
+) -> Option<&'a SyntaxReference> {
+    let command = first_line.strip_prefix("#!")?.trim();
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    // env runs the program named after it, skipping its own options (e.g. env -S)
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == interpreter)
+        .map_or(interpreter, |&(_, language)| language);
+    find_syntax_by_language(ps, language)
+}
+
+// Interpreters whose name doesn't match their language's syntax, and the extension of the
+// syntax to use for them
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("ash", "sh"),
+    ("bash", "sh"),
+    ("bun", "js"),
+    ("cargo", "rs"),
+    ("clisp", "lisp"),
+    ("dash", "sh"),
+    ("deno", "js"),
+    ("escript", "erl"),
+    ("ksh", "sh"),
+    ("make", "Makefile"),
+    ("mksh", "sh"),
+    ("node", "js"),
+    ("nodejs", "js"),
+    ("Rscript", "r"),
+    ("runghc", "hs"),
+    ("runhaskell", "hs"),
+    ("rust-script", "rs"),
+    ("sbcl", "lisp"),
+    ("tclsh", "tcl"),
+    ("wish", "tcl"),
+    ("zsh", "sh"),
+];
+
+// Lines at the start and end of a file that are searched for modelines, like vim does
+const MODELINE_LINES: usize = 5;
+// How much of the end of a file is read to find its last lines
+const MODELINE_TAIL: u64 = 4096;
+
+// Look for a vim ("vim: ft=yaml") or emacs ("-*- mode: ruby -*-") modeline in the first
+// lines of `head`, then in the last lines of the file at `file_path`, and look up the
+// syntax for the file type it names
+pub fn find_syntax_by_modeline<'a>(
+    ps: &'a SyntaxSet,
+    file_path: Option<&Path>,
+    head: &str,
+) -> Option<&'a SyntaxReference> {
+    let tail = file_path.and_then(read_tail).unwrap_or_default();
+    let first_lines = head.lines().take(MODELINE_LINES);
+    let last_lines = tail.lines().rev().take(MODELINE_LINES);
+    let file_type = first_lines
+        .chain(last_lines)
+        .find_map(|line| vim_file_type(line).or_else(|| emacs_mode(line)))?;
+    let language = MODE_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(file_type))
+        .map_or(file_type, |&(_, language)| language);
+    find_syntax_by_language(ps, language)
+}
+
+// The last few KiB of the file at `path`. Only regular files are read, since reading e.g.
+// a named pipe would use up its content
+fn read_tail(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let metadata = file.metadata().ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    file.seek(SeekFrom::Start(
+        metadata.len().saturating_sub(MODELINE_TAIL),
+    ))
+    .ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// The file type set by a vim modeline, e.g. "yaml" for "# vim: set ft=yaml ts=2:"
+fn vim_file_type(line: &str) -> Option<&str> {
+    // The marker has to start the line or follow whitespace, so "xvim:" doesn't count
+    let start = ["vim:", "vi:", "Vim:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|&(index, _)| {
+                line[..index]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+            })
+            .map(|(index, _)| index + marker.len())
+    })?;
+    let options = line[start..].trim_start();
+    let options = options
+        .strip_prefix("set ")
+        .or_else(|| options.strip_prefix("se "))
+        .unwrap_or(options);
+    options
+        .split([' ', '\t', ':'])
+        .filter_map(|option| option.split_once('='))
+        .find(|(key, _)| matches!(*key, "ft" | "filetype" | "syn" | "syntax"))
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+// The major mode set by an emacs modeline, e.g. "ruby" for "-*- mode: ruby -*-" or just
+// "-*- ruby -*-"
+fn emacs_mode(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (variables, _) = rest.split_once("-*-")?;
+    let variables = variables.trim();
+    if !variables.contains(':') {
+        return Some(variables).filter(|mode| !mode.is_empty());
+    }
+    variables
+        .split(';')
+        .filter_map(|variable| variable.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("mode"))
+        .map(|(_, mode)| mode.trim())
+        .filter(|mode| !mode.is_empty())
+}
+
+// File types and modes whose name isn't also a syntax name or extension, and the extension
+// of the syntax to use for them
+const MODE_NAMES: &[(&str, &str)] = &[
+    ("c++", "cpp"),
+    ("cperl", "pl"),
+    ("emacs-lisp", "lisp"),
+    ("javascript", "js"),
+    ("js2", "js"),
+    ("make", "Makefile"),
+    ("makefile", "Makefile"),
+    ("shell-script", "sh"),
+    ("zsh", "sh"),
+];
+
+// Recognize a few common formats from how their content starts, for inputs that nothing
+// else identified: XML and HTML documents, JSON (as long as the start parses as JSON),
+// YAML documents and front matter, and diffs
+pub fn find_syntax_by_content<'a>(ps: &'a SyntaxSet, content: &str) -> Option<&'a SyntaxReference> {
+    let start = content.trim_start();
+    let first_line = start.lines().next()?.trim_end();
+    let lowercase = first_line.to_ascii_lowercase();
+    let language = if start.starts_with("<?xml") {
+        "xml"
+    } else if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        "html"
+    } else if (start.starts_with('{') || start.starts_with('[')) && is_json_prefix(start) {
+        "json"
+    } else if first_line == "---" || first_line.starts_with("%YAML") {
+        "yaml"
+    } else if first_line.starts_with("diff --git ")
+        || (first_line.starts_with("--- ") && start.lines().nth(1)?.starts_with("+++ "))
+    {
+        "diff"
+    } else {
+        return None;
+    };
+    find_syntax_by_language(ps, language)
+}
+
+// Whether `text` is JSON, or the start of a JSON value that was cut off (detection only
+// sees the start of an input)
+fn is_json_prefix(text: &str) -> bool {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<serde_json::Value>();
+    match values.next() {
+        Some(Ok(_)) => true,
+        Some(Err(err)) => err.is_eof(),
+        None => false,
+    }
+}
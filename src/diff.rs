@@ -0,0 +1,285 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use similar::{ChangeTag, TextDiff};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Style, Theme};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::error::CathError;
+use crate::terminal::{self, ColorDepth};
+
+// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+// Background tints for removed and added lines: a dark red and a dark green, with the
+// palette entries to use in their place when there are fewer colors. Quantizing the tints
+// themselves would turn both into the same grey (or black, with 16 colors)
+const REMOVED_BACKGROUND: Tint = Tint {
+    color: Color {
+        r: 72,
+        g: 20,
+        b: 24,
+        a: 0xff,
+    },
+    ansi256: 52,
+    ansi16: 1,
+};
+const ADDED_BACKGROUND: Tint = Tint {
+    color: Color {
+        r: 20,
+        g: 56,
+        b: 28,
+        a: 0xff,
+    },
+    ansi256: 22,
+    ansi16: 2,
+};
+
+struct Tint {
+    color: Color,
+    // Index into the 256-color palette
+    ansi256: u8,
+    // Index of one of the 8 basic colors
+    ansi16: u8,
+}
+
+// The escape that sets the background to `tint` at `depth`
+fn tint_escape(tint: &Tint, depth: ColorDepth) -> String {
+    match depth {
+        ColorDepth::TrueColor | ColorDepth::Auto => terminal::background_escape(tint.color, depth),
+        ColorDepth::Ansi256 => format!("\x1b[48;5;{}m", tint.ansi256),
+        ColorDepth::Ansi16 => format!("\x1b[{}m", 40 + tint.ansi16),
+    }
+}
+
+// How the diff is laid out on the terminal
+pub struct DiffLayout {
+    // Two columns instead of a unified diff
+    pub side_by_side: bool,
+    // Terminal width, split between the columns in side-by-side mode
+    pub width: usize,
+    pub color_depth: ColorDepth,
+}
+
+// One side of the comparison: the file's label and its highlighted lines
+pub struct DiffSide<'a> {
+    pub name: String,
+    pub lines: Vec<Vec<(Style, &'a str)>>,
+}
+
+impl<'a> DiffSide<'a> {
+    // Highlight every line of `content`, the file at `path`, up front so both sides keep
+    // correct parse state
+    pub fn new(
+        path: &Path,
+        content: &'a str,
+        syntax: &SyntaxReference,
+        ps: &SyntaxSet,
+        theme: &Theme,
+    ) -> Result<Self, CathError> {
+        let mut h = HighlightLines::new(syntax, theme);
+        let lines = LinesWithEndings::from(content)
+            .map(|line| {
+                h.highlight_line(line, ps)
+                    .map_err(|err| CathError::highlight(Some(path), err))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DiffSide {
+            name: path.display().to_string(),
+            lines,
+        })
+    }
+}
+
+// Print a diff of `old_text` against `new_text`, highlighting both sides, either as a unified
+// diff or as two columns
+pub fn print_diff(
+    handle: &mut dyn Write,
+    old_text: &str,
+    new_text: &str,
+    old: &DiffSide,
+    new: &DiffSide,
+    layout: &DiffLayout,
+) -> io::Result<()> {
+    let diff = TextDiff::from_lines(old_text, new_text);
+    let depth = layout.color_depth;
+    let removed = tint_escape(&REMOVED_BACKGROUND, depth);
+    let added = tint_escape(&ADDED_BACKGROUND, depth);
+
+    writeln!(handle, "\x1b[1;31m--- {}\x1b[0m", old.name)?;
+    writeln!(handle, "\x1b[1;32m+++ {}\x1b[0m", new.name)?;
+
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+        writeln!(
+            handle,
+            "\x1b[36m@@ -{},{} +{},{} @@\x1b[0m",
+            old_range.start + 1,
+            old_range.len(),
+            new_range.start + 1,
+            new_range.len()
+        )?;
+
+        for op in &group {
+            if layout.side_by_side {
+                print_side_by_side(handle, old, new, op, layout, (&removed, &added))?;
+                continue;
+            }
+            for change in diff.iter_changes(op) {
+                match change.tag() {
+                    ChangeTag::Equal => {
+                        let index = change.old_index().unwrap_or_default();
+                        print_unified_line(handle, ' ', "", &old.lines[index], depth)?;
+                    }
+                    ChangeTag::Delete => {
+                        let index = change.old_index().unwrap_or_default();
+                        print_unified_line(handle, '-', &removed, &old.lines[index], depth)?;
+                    }
+                    ChangeTag::Insert => {
+                        let index = change.new_index().unwrap_or_default();
+                        print_unified_line(handle, '+', &added, &new.lines[index], depth)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Write one unified diff line: the +/-/space marker and the highlighted text on `background`
+// (\x1b[K extends the tint to the edge of the terminal)
+fn print_unified_line(
+    handle: &mut dyn Write,
+    marker: char,
+    background: &str,
+    ranges: &[(Style, &str)],
+    depth: ColorDepth,
+) -> io::Result<()> {
+    let text = terminal::as_terminal_escaped(ranges, false, depth);
+    let text = text.trim_end_matches(['\n', '\r']);
+    writeln!(handle, "{}{}{}\x1b[K\x1b[0m", background, marker, text)
+}
+
+// Write the rows for one diff operation in two columns: old lines on the left, new lines on
+// the right, pairing up replaced lines; `tints` holds the removed and added backgrounds
+fn print_side_by_side(
+    handle: &mut dyn Write,
+    old: &DiffSide,
+    new: &DiffSide,
+    op: &similar::DiffOp,
+    layout: &DiffLayout,
+    tints: (&str, &str),
+) -> io::Result<()> {
+    // Each column gets half the width minus the line number and the separator
+    let column = (layout.width.saturating_sub(3) / 2)
+        .saturating_sub(5)
+        .max(10);
+    let old_range = op.old_range();
+    let new_range = op.new_range();
+    let is_equal = matches!(op, similar::DiffOp::Equal { .. });
+    let rows = old_range.len().max(new_range.len());
+
+    for row in 0..rows {
+        let left = old_range.clone().nth(row).map(|index| (index, tints.0));
+        let right = new_range.clone().nth(row).map(|index| (index, tints.1));
+
+        let cell = |side: &DiffSide, entry: Option<(usize, &str)>| match entry {
+            Some((index, background)) => {
+                let background = if is_equal { "" } else { background };
+                format!(
+                    "{}{:4} {}\x1b[0m",
+                    background,
+                    index + 1,
+                    fit_to_width(&side.lines[index], column, layout.color_depth)
+                )
+            }
+            None => " ".repeat(column + 5),
+        };
+        writeln!(
+            handle,
+            "{} \x1b[2m│\x1b[0m {}",
+            cell(old, left),
+            cell(new, right)
+        )?;
+    }
+    Ok(())
+}
+
+// Render highlighted ranges clipped or padded to exactly `width` columns; tabs are expanded
+// so the padding lines up
+fn fit_to_width(ranges: &[(Style, &str)], width: usize, depth: ColorDepth) -> String {
+    let mut clipped: Vec<(Style, String)> = Vec::new();
+    let mut used = 0;
+    for (style, text) in ranges {
+        let mut piece = String::new();
+        for c in text.chars() {
+            if c == '\n' || c == '\r' {
+                continue;
+            }
+            if c == '\t' {
+                let spaces = 4 - used % 4;
+                for _ in 0..spaces {
+                    if used < width {
+                        piece.push(' ');
+                        used += 1;
+                    }
+                }
+            } else if used < width {
+                piece.push(c);
+                used += 1;
+            }
+        }
+        clipped.push((*style, piece));
+    }
+    let borrowed: Vec<(Style, &str)> = clipped
+        .iter()
+        .map(|(style, text)| (*style, text.as_str()))
+        .collect();
+    let mut out = terminal::as_terminal_escaped(&borrowed, false, depth);
+    out.push_str(&" ".repeat(width - used));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_and_added_tints_differ_at_every_depth() {
+        for depth in [
+            ColorDepth::TrueColor,
+            ColorDepth::Ansi256,
+            ColorDepth::Ansi16,
+        ] {
+            assert_ne!(
+                tint_escape(&REMOVED_BACKGROUND, depth),
+                tint_escape(&ADDED_BACKGROUND, depth)
+            );
+        }
+    }
+
+    #[test]
+    fn tints_fall_back_to_red_and_green() {
+        assert_eq!(
+            tint_escape(&REMOVED_BACKGROUND, ColorDepth::Ansi256),
+            "\x1b[48;5;52m"
+        );
+        assert_eq!(
+            tint_escape(&ADDED_BACKGROUND, ColorDepth::Ansi256),
+            "\x1b[48;5;22m"
+        );
+        assert_eq!(
+            tint_escape(&REMOVED_BACKGROUND, ColorDepth::Ansi16),
+            "\x1b[41m"
+        );
+        assert_eq!(
+            tint_escape(&ADDED_BACKGROUND, ColorDepth::Ansi16),
+            "\x1b[42m"
+        );
+    }
+}
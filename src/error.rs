@@ -0,0 +1,154 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// What can go wrong printing an input. The command reports it on stderr and goes on with
+// the other inputs, exiting with status 1 once they're done. Notices come the same way,
+// but don't count as failing
+#[derive(Debug)]
+pub enum CathError {
+    // An input (None for stdin) couldn't be opened or read, or its lines written out
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    // Getting at what's in an input failed some other way, e.g. fetching a URL, opening
+    // an archive or running a --query on it
+    Input {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    // The syntax definition failed on a line of an input (None for code handed to the
+    // library, which has no name)
+    Highlight {
+        path: Option<PathBuf>,
+        source: syntect::Error,
+    },
+    // Walking a directory for --recursive failed: an --include or --exclude glob isn't
+    // valid (None when building them all together failed), or a directory couldn't be read
+    Walk {
+        glob: Option<String>,
+        source: ignore::Error,
+    },
+    UnknownTheme(String),
+    // Not an error, but something worth telling about an input: that it was left out
+    // for being binary, where --find found its match, or that a preprocessor failed and
+    // the file was read as it is
+    Notice {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    UnknownLanguage(String),
+    // Writing to the output failed
+    Output(io::Error),
+    // Watching the inputs for --watch failed, or redrawing them did
+    Watch(io::Error),
+}
+
+impl CathError {
+    pub fn io(path: Option<&Path>, source: io::Error) -> Self {
+        CathError::Io {
+            path: path.map(Path::to_path_buf),
+            source,
+        }
+    }
+
+    // Whether this is the output being closed by whatever reads it, which is how `head`
+    // says it has seen enough rather than something worth reporting
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(
+            self,
+            CathError::Io { source, .. } | CathError::Output(source) | CathError::Watch(source)
+                if source.kind() == io::ErrorKind::BrokenPipe
+        )
+    }
+
+    pub fn highlight(path: Option<&Path>, source: impl Into<syntect::Error>) -> Self {
+        CathError::Highlight {
+            path: path.map(Path::to_path_buf),
+            source: source.into(),
+        }
+    }
+
+    pub fn input(path: Option<&Path>, message: impl fmt::Display) -> Self {
+        CathError::Input {
+            path: path.map(Path::to_path_buf),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn notice(path: Option<&Path>, message: impl fmt::Display) -> Self {
+        CathError::Notice {
+            path: path.map(Path::to_path_buf),
+            message: message.to_string(),
+        }
+    }
+
+    // Whether this is only a notice, which doesn't make the command fail
+    pub fn is_notice(&self) -> bool {
+        matches!(self, CathError::Notice { .. })
+    }
+}
+
+impl fmt::Display for CathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map_or("STDIN".into(), |path| path.display().to_string())
+        };
+        match self {
+            CathError::Io { path, source } => write!(f, "{}: {}", name(path), reason(source)),
+            CathError::Input { path, message } | CathError::Notice { path, message } => {
+                write!(f, "{}: {}", name(path), message)
+            }
+            CathError::Highlight {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: failed to highlight: {}", path.display(), source),
+            CathError::Highlight { path: None, source } => {
+                write!(f, "failed to highlight: {}", source)
+            }
+            CathError::Walk {
+                glob: Some(glob),
+                source,
+            } => write!(f, "invalid glob '{}': {}", glob, source),
+            CathError::Walk { glob: None, source } => write!(f, "{}", source),
+            CathError::UnknownTheme(theme) => write!(f, "unknown theme '{}'", theme),
+            CathError::UnknownLanguage(language) => write!(
+                f,
+                "unknown language '{}' (use a syntax name or file extension)",
+                language
+            ),
+            CathError::Output(source) => {
+                write!(f, "failed to write the output: {}", reason(source))
+            }
+            CathError::Watch(source) => write!(f, "failed to watch the files: {}", reason(source)),
+        }
+    }
+}
+
+impl std::error::Error for CathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CathError::Io { source, .. } | CathError::Output(source) | CathError::Watch(source) => {
+                Some(source)
+            }
+            CathError::Highlight { source, .. } => Some(source),
+            CathError::Walk { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// What went wrong, the way cat would put it: without the "(os error 21)" that io::Error
+// adds to messages from the system, and with a pointer to --recursive for directories
+fn reason(err: &io::Error) -> String {
+    let message = err.to_string();
+    if err.kind() == io::ErrorKind::IsADirectory {
+        return "Is a directory (--recursive prints the files in it)".to_string();
+    }
+    match message.find(" (os error ") {
+        Some(end) if err.raw_os_error().is_some() => message[..end].to_string(),
+        _ => message,
+    }
+}
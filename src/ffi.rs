@@ -0,0 +1,120 @@
+// C bindings to the highlighter, for tools in other languages to call in-process. The
+// header in include/cath.h is generated from this file by the build script, so these
+// functions are documented with /// comments, which cbindgen carries over into it
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic;
+use std::ptr;
+
+use crate::{CathError, DEFAULT_THEME, render_ansi, render_html};
+
+thread_local! {
+    // What went wrong in the last call on this thread that returned NULL
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Highlight `code` with 24-bit terminal color escapes.
+///
+/// `language` is a syntax name or file extension (e.g. "Rust" or "rs"), or NULL to detect
+/// it from the code. `theme` is the name of a color theme, or NULL for the default one.
+/// Returns a string to free with cath_free_string, or NULL on error (see cath_last_error).
+///
+/// # Safety
+///
+/// `code` must be a NUL-terminated UTF-8 string, and `language` and `theme` either NULL or
+/// NUL-terminated UTF-8 strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cath_render_ansi(
+    code: *const c_char,
+    language: *const c_char,
+    theme: *const c_char,
+) -> *mut c_char {
+    unsafe { render(code, language, theme, render_ansi) }
+}
+
+/// Highlight `code` as an HTML <pre> block with inline styles.
+///
+/// Takes the same arguments, and returns the same way, as cath_render_ansi.
+///
+/// # Safety
+///
+/// The same as for cath_render_ansi.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cath_render_html(
+    code: *const c_char,
+    language: *const c_char,
+    theme: *const c_char,
+) -> *mut c_char {
+    unsafe { render(code, language, theme, render_html) }
+}
+
+/// Free a string returned by cath_render_ansi or cath_render_html. NULL is ignored.
+///
+/// # Safety
+///
+/// `string` must have come from one of those functions, and not been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cath_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// The message for the last error on this thread, or NULL when there hasn't been one.
+///
+/// The string belongs to the library and stays valid until the next call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn cath_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+// Run `renderer` on the C strings it's called with, turning its result into a string for
+// the caller to free, or NULL with the error kept for cath_last_error. A panic can't be
+// let out into the C caller, which would abort it, so it's turned into an error as well
+unsafe fn render(
+    code: *const c_char,
+    language: *const c_char,
+    theme: *const c_char,
+    renderer: fn(&str, Option<&str>, &str) -> Result<String, CathError>,
+) -> *mut c_char {
+    let result = unsafe { (argument(code), argument(language), argument(theme)) };
+    let result = match result {
+        (Ok(Some(code)), Ok(language), Ok(theme)) => {
+            match panic::catch_unwind(|| renderer(code, language, theme.unwrap_or(DEFAULT_THEME))) {
+                Ok(result) => result.map_err(|err| err.to_string()),
+                Err(_) => Err("the highlighter panicked".to_string()),
+            }
+        }
+        (Ok(None), _, _) => Err("code is NULL".to_string()),
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => Err(err),
+    };
+    let result =
+        result.and_then(|output| CString::new(output).map_err(|_| "output has a NUL".to_string()));
+    match result {
+        Ok(output) => {
+            LAST_ERROR.with(|error| error.borrow_mut().take());
+            output.into_raw()
+        }
+        Err(err) => {
+            LAST_ERROR.with(|error| *error.borrow_mut() = CString::new(err).ok());
+            ptr::null_mut()
+        }
+    }
+}
+
+// Read a C string argument, None for NULL
+unsafe fn argument<'a>(string: *const c_char) -> Result<Option<&'a str>, String> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .map(Some)
+        .map_err(|_| "argument is not valid UTF-8".to_string())
+}
@@ -0,0 +1,59 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::CathError;
+use crate::input;
+
+// How long to wait before checking the file for new data again
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Keep `path` open and hand every line appended after byte `offset` to `print_line`, like
+// `tail -f`. Partial lines are held back until their newline arrives, and if the file shrinks
+// (e.g. a log was truncated) reading starts over from the beginning. This never returns
+// unless reading or writing fails. Lines that aren't valid UTF-8 are an error, unless
+// `lossy` is set
+pub fn follow(
+    handle: &mut impl Write,
+    path: &Path,
+    offset: u64,
+    lossy: bool,
+    mut print_line: impl FnMut(&mut dyn Write, &str) -> Result<(), CathError>,
+) -> Result<(), CathError> {
+    let read_error = |err| CathError::io(Some(path), err);
+    let mut file = File::open(path).map_err(read_error)?;
+    file.seek(SeekFrom::Start(offset)).map_err(read_error)?;
+    let mut position = offset;
+    let mut reader = BufReader::new(file);
+    // Raw bytes, since a partial line may end partway through a multi-byte character
+    let mut line = Vec::new();
+
+    loop {
+        let read = reader.read_until(b'\n', &mut line).map_err(read_error)?;
+        if read > 0 {
+            position += read as u64;
+            if line.ends_with(b"\n") {
+                let decoded =
+                    input::decode(std::mem::take(&mut line), lossy).map_err(read_error)?;
+                print_line(handle, &decoded)?;
+            }
+            continue;
+        }
+
+        // Nothing new: make sure everything printed so far is visible, then wait
+        handle.flush().map_err(CathError::Output)?;
+        thread::sleep(POLL_INTERVAL);
+
+        let length = std::fs::metadata(path).map_err(read_error)?.len();
+        if length < position {
+            // The file was truncated or replaced, so start over with a fresh handle
+            let mut file = File::open(path).map_err(read_error)?;
+            file.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            reader = BufReader::new(file);
+            position = 0;
+            line.clear();
+        }
+    }
+}
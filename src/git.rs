@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+// How a line of the working tree file differs from the version in the git index
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    // One or more lines were deleted right after this one (or before line 1)
+    Removed,
+}
+
+impl LineChange {
+    // Gutter marker for this change, colored green/yellow/red
+    pub fn marker(self) -> &'static str {
+        match self {
+            LineChange::Added => "\x1b[32m+\x1b[0m",
+            LineChange::Modified => "\x1b[33m~\x1b[0m",
+            LineChange::Removed => "\x1b[31m-\x1b[0m",
+        }
+    }
+}
+
+// Changed lines of a file, keyed by 1-based line number
+pub type LineChanges = HashMap<usize, LineChange>;
+
+// Compare `path` against the git index by running `git diff`
+// Returns None when the file isn't inside a git repository or git isn't installed; an
+// untracked or unchanged file gives an empty map
+pub fn line_changes(path: &Path) -> Option<LineChanges> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let file_name = path.file_name()?;
+
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // -U0 drops the context lines so every hunk header describes exactly the changed lines
+    let output = command
+        .args(["diff", "--no-color", "--no-ext-diff", "-U0", "--"])
+        .arg(file_name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let diff = String::from_utf8_lossy(&output.stdout);
+    let mut changes = LineChanges::new();
+    for line in diff.lines() {
+        let Some((old_count, new_start, new_count)) = parse_hunk_header(line) else {
+            continue;
+        };
+        if new_count == 0 {
+            // Pure deletion: mark the line the removed lines used to follow
+            changes.insert(new_start.max(1), LineChange::Removed);
+        } else {
+            let kind = if old_count == 0 {
+                LineChange::Added
+            } else {
+                LineChange::Modified
+            };
+            for line_number in new_start..new_start + new_count {
+                changes.insert(line_number, kind);
+            }
+        }
+    }
+    Some(changes)
+}
+
+// Parse a unified diff hunk header like "@@ -12,3 +12,4 @@ fn main()" into
+// (old line count, new start line, new line count); a missing count means 1
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let ranges = line.strip_prefix("@@ -")?;
+    let (old, rest) = ranges.split_once(" +")?;
+    let (new, _) = rest.split_once(" @@")?;
+
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (_, old_count) = parse_range(old)?;
+    let (new_start, new_count) = parse_range(new)?;
+    Some((old_count, new_start, new_count))
+}
+
+// Who last changed a line, as reported by `git blame`
+pub struct BlameLine {
+    // Abbreviated commit hash
+    pub hash: String,
+    pub author: String,
+    // Author date as YYYY-MM-DD
+    pub date: String,
+}
+
+// Width the author name is padded or truncated to in the blame column
+const BLAME_AUTHOR_WIDTH: usize = 12;
+
+impl BlameLine {
+    // Dimmed "hash author date" annotation for the gutter
+    pub fn annotation(&self) -> String {
+        let author: String = self.author.chars().take(BLAME_AUTHOR_WIDTH).collect();
+        format!(
+            "\x1b[2m{} {:width$} {}\x1b[0m",
+            self.hash,
+            author,
+            self.date,
+            width = BLAME_AUTHOR_WIDTH
+        )
+    }
+
+    // Blank annotation of the same width, for lines git doesn't know about (e.g. appended
+    // while following the file)
+    pub fn blank_annotation() -> String {
+        " ".repeat(8 + 1 + BLAME_AUTHOR_WIDTH + 1 + 10)
+    }
+}
+
+// Run `git blame` on `path` and return one entry per line of the working tree file
+// Returns None when the file isn't tracked by git or git isn't installed
+pub fn blame(path: &Path) -> Option<Vec<BlameLine>> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let file_name = path.file_name()?;
+
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // --line-porcelain repeats the full commit details for every line, which makes it
+    // possible to parse each line on its own
+    let output = command
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(file_name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let porcelain = String::from_utf8_lossy(&output.stdout);
+    let mut lines = Vec::new();
+    let mut hash = String::new();
+    let mut author = String::new();
+    let mut date = String::new();
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            // The tab-prefixed line is the file content and ends the entry
+            lines.push(BlameLine {
+                hash: std::mem::take(&mut hash),
+                author: std::mem::take(&mut author),
+                date: std::mem::take(&mut date),
+            });
+        } else if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            date = time.parse().map(format_date).unwrap_or_default();
+        } else if hash.is_empty()
+            && line.len() >= 40
+            && line.as_bytes()[..40].iter().all(u8::is_ascii_hexdigit)
+        {
+            // Entry header: "<40 hex digit hash> <original line> <final line> [<group size>]"
+            hash = line[..8].to_string();
+        }
+    }
+    Some(lines)
+}
+
+// Format a Unix timestamp as a UTC YYYY-MM-DD date
+pub fn format_date(timestamp: i64) -> String {
+    // Convert days since the epoch to a civil date (Howard Hinnant's algorithm)
+    let days = timestamp.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
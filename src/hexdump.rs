@@ -0,0 +1,99 @@
+use std::io::{self, Read, Write};
+
+// Bytes shown on each row of the dump
+const BYTES_PER_ROW: usize = 16;
+
+// Colors for the byte classes, used in both the hex and the character panel
+const NUL: &str = "\x1b[2m";
+const PRINTABLE: &str = "\x1b[36m";
+const WHITESPACE: &str = "\x1b[32m";
+const CONTROL: &str = "\x1b[35m";
+const HIGH_BIT: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+// Write a hex dump of everything `reader` yields: the offset, the bytes in hex (split into
+// two groups of eight) and the bytes as characters, with each byte colored by its class
+// Runs of identical rows are collapsed into a single "*", like hexdump -C does
+pub fn print_hexdump(handle: &mut dyn Write, reader: &mut dyn Read) -> io::Result<()> {
+    let mut offset = 0;
+    let mut previous: Option<[u8; BYTES_PER_ROW]> = None;
+    let mut squeezing = false;
+    loop {
+        let mut row = [0; BYTES_PER_ROW];
+        let len = read_row(reader, &mut row)?;
+        if len == 0 {
+            break;
+        }
+        if len == BYTES_PER_ROW && previous == Some(row) {
+            if !squeezing {
+                writeln!(handle, "*")?;
+                squeezing = true;
+            }
+        } else {
+            print_row(handle, offset, &row[..len])?;
+            squeezing = false;
+        }
+        previous = Some(row);
+        offset += len;
+        if len < BYTES_PER_ROW {
+            break;
+        }
+    }
+    // The final offset shows the total size
+    writeln!(handle, "\x1b[2m{:08x}{}", offset, RESET)
+}
+
+// Fill `row` as far as the input allows, returning how many bytes were read (fewer than
+// the row size only at the end of the input)
+fn read_row(reader: &mut dyn Read, row: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < row.len() {
+        match reader.read(&mut row[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+// Write one row: offset, hex panel and character panel
+fn print_row(handle: &mut dyn Write, offset: usize, bytes: &[u8]) -> io::Result<()> {
+    write!(handle, "\x1b[2m{:08x}{}  ", offset, RESET)?;
+    for index in 0..BYTES_PER_ROW {
+        match bytes.get(index) {
+            Some(&byte) => write!(handle, "{}{:02x}{} ", byte_color(byte), byte, RESET)?,
+            None => write!(handle, "   ")?,
+        }
+        // Extra space between the two groups of eight
+        if index == BYTES_PER_ROW / 2 - 1 {
+            write!(handle, " ")?;
+        }
+    }
+    write!(handle, " |")?;
+    for &byte in bytes {
+        write!(handle, "{}{}{}", byte_color(byte), byte_char(byte), RESET)?;
+    }
+    writeln!(handle, "|")
+}
+
+// The color for a byte's class
+fn byte_color(byte: u8) -> &'static str {
+    match byte {
+        0 => NUL,
+        b' ' | b'\t' | b'\n' | b'\r' => WHITESPACE,
+        _ if byte.is_ascii_graphic() => PRINTABLE,
+        _ if byte.is_ascii() => CONTROL,
+        _ => HIGH_BIT,
+    }
+}
+
+// How a byte appears in the character panel; anything that isn't printable shows as a dot
+fn byte_char(byte: u8) -> char {
+    if byte == b' ' || byte.is_ascii_graphic() {
+        byte as char
+    } else {
+        '.'
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use syntect::highlighting::{Color, FontStyle, Highlighter, Style, Theme};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::assets::HighlightingAssets;
+use crate::detect::{detect_syntax, find_syntax_by_language};
+use crate::error::CathError;
+use crate::terminal::{self, ColorDepth};
+use crate::{highlight_lines, html, within_highlight_budget};
+
+thread_local! {
+    // Loaded on first use and kept, since callers tend to highlight one snippet after
+    // another and loading the syntaxes takes longer than highlighting does
+    static ASSETS: HighlightingAssets = HighlightingAssets::load();
+}
+
+// A piece of highlighted code, as tokenize returns it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    // Byte offsets into the code, the end excluded
+    pub start: usize,
+    pub end: usize,
+    pub foreground: Color,
+    pub background: Color,
+    pub font_style: FontStyle,
+    // The scopes the span is in, outermost first and separated by spaces, as in
+    // "source.rust meta.function.rust keyword.other.fn.rust"
+    pub scope: String,
+}
+
+// Splits code into spans a line at a time, keeping the parse state from one line to the
+// next. The spans are those the theme highlights, broken up further where the scopes
+// change underneath a style
+pub(crate) struct Tokenizer<'a> {
+    // The input the code comes from, for errors
+    file_path: Option<&'a Path>,
+    ps: &'a SyntaxSet,
+    highlighter: Highlighter<'a>,
+    parse_state: ParseState,
+    stack: ScopeStack,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(
+        file_path: Option<&'a Path>,
+        syntax: &SyntaxReference,
+        ps: &'a SyntaxSet,
+        theme: &'a Theme,
+    ) -> Self {
+        Tokenizer {
+            file_path,
+            ps,
+            highlighter: Highlighter::new(theme),
+            parse_state: ParseState::new(syntax),
+            stack: ScopeStack::new(),
+        }
+    }
+
+    // The spans of `line`, line ending included, with offsets counted from its start.
+    // Lines over the length budget are a single span in the theme's plain text style,
+    // unless `strict` is set
+    pub fn line(&mut self, line: &str, strict: bool) -> Result<Vec<Span>, CathError> {
+        if !within_highlight_budget(line, strict) {
+            let style = self.highlighter.get_default();
+            return Ok(vec![self.span(0, line.len(), style)]);
+        }
+        let ops = self
+            .parse_state
+            .parse_line(line, self.ps)
+            .map_err(|err| CathError::highlight(self.file_path, err))?;
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for (position, op) in ops {
+            if position > start {
+                spans.push(self.styled_span(start, position));
+                start = position;
+            }
+            self.stack
+                .apply(&op)
+                .map_err(|err| CathError::highlight(self.file_path, err))?;
+        }
+        if start < line.len() {
+            spans.push(self.styled_span(start, line.len()));
+        }
+        Ok(spans)
+    }
+
+    // The span from `start` to `end` in the style the current scopes give it
+    fn styled_span(&self, start: usize, end: usize) -> Span {
+        let style = self.highlighter.style_for_stack(self.stack.as_slice());
+        self.span(start, end, style)
+    }
+
+    fn span(&self, start: usize, end: usize, style: Style) -> Span {
+        let scope = self
+            .stack
+            .as_slice()
+            .iter()
+            .map(|scope| scope.build_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Span {
+            start,
+            end,
+            foreground: style.foreground,
+            background: style.background,
+            font_style: style.font_style,
+            scope,
+        }
+    }
+}
+
+// Highlight `code` as `language` (a syntax name or file extension, or detected from the
+// code itself when None) with the theme named `theme`, and return it as a <pre> block
+// with inline styles, as --to-html --html-fragment writes it
+pub fn render_html(code: &str, language: Option<&str>, theme: &str) -> Result<String, CathError> {
+    with_highlighting(code, language, theme, |ps, syntax, theme| {
+        let lines = highlight_lines(code, None, syntax, ps, theme, &[], false)?;
+        Ok(html::render_pre(theme, &lines, false))
+    })
+}
+
+// Highlight `code` like render_html does, and return it with 24-bit color escapes for a
+// terminal, as cath prints it without decorations
+pub fn render_ansi(code: &str, language: Option<&str>, theme: &str) -> Result<String, CathError> {
+    with_highlighting(code, language, theme, |ps, syntax, theme| {
+        let mut out = String::with_capacity(code.len() * 2);
+        for (_, ranges) in highlight_lines(code, None, syntax, ps, theme, &[], false)? {
+            out.push_str(&terminal::as_terminal_escaped(
+                &ranges,
+                false,
+                ColorDepth::TrueColor,
+            ));
+        }
+        out.push_str("\x1b[0m");
+        Ok(out)
+    })
+}
+
+// Highlight `code` like render_html does, and return the styled spans it's made of, in
+// order. Together they cover all of the code, line endings included
+pub fn tokenize(code: &str, language: Option<&str>, theme: &str) -> Result<Vec<Span>, CathError> {
+    with_highlighting(code, language, theme, |ps, syntax, theme| {
+        let mut tokenizer = Tokenizer::new(None, syntax, ps, theme);
+        let mut spans = Vec::new();
+        let mut offset = 0;
+        for line in LinesWithEndings::from(code) {
+            spans.extend(tokenizer.line(line, false)?.into_iter().map(|span| Span {
+                start: offset + span.start,
+                end: offset + span.end,
+                ..span
+            }));
+            offset += line.len();
+        }
+        Ok(spans)
+    })
+}
+
+// Look up the syntax and theme to highlight `code` with, the way the command line does,
+// and hand them to `highlight`
+fn with_highlighting<T>(
+    code: &str,
+    language: Option<&str>,
+    theme: &str,
+    highlight: impl FnOnce(&SyntaxSet, &SyntaxReference, &Theme) -> Result<T, CathError>,
+) -> Result<T, CathError> {
+    ASSETS.with(|assets| {
+        let ps = assets.syntax_set();
+        let theme = assets
+            .theme_set
+            .themes
+            .get(theme)
+            .ok_or_else(|| CathError::UnknownTheme(theme.to_string()))?;
+        let syntax = match language {
+            Some(language) => find_syntax_by_language(ps, language)
+                .ok_or_else(|| CathError::UnknownLanguage(language.to_string()))?,
+            None => detect_syntax(ps, None, code, None, &[]),
+        };
+        highlight(ps, syntax, theme)
+    })
+}
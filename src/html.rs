@@ -0,0 +1,83 @@
+use std::fmt::Write as _;
+
+use syntect::highlighting::{Color, Style, Theme};
+use syntect::html::{IncludeBackground, styled_line_to_highlighted_html};
+
+// Opening of a standalone document; the theme colors are applied to the whole page
+pub fn document_start(theme: &Theme, title: &str) -> String {
+    let background = theme.settings.background.unwrap_or(Color::WHITE);
+    let foreground = theme.settings.foreground.unwrap_or(Color::BLACK);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n\
+         <body style=\"background-color:{};color:{};\">\n",
+        escape(title),
+        css_color(background),
+        css_color(foreground)
+    )
+}
+
+// Closing of a standalone document
+pub fn document_end() -> &'static str {
+    "</body>\n</html>\n"
+}
+
+// Heading shown above a file when headers are enabled
+pub fn file_header(name: &str) -> String {
+    format!(
+        "<h3 style=\"font-family:monospace;\">{}</h3>\n",
+        escape(name)
+    )
+}
+
+// Render highlighted lines as a <pre> block with inline styles, optionally prefixing each
+// line with its (dimmed, unselectable) line number
+pub fn render_pre(
+    theme: &Theme,
+    lines: &[(usize, Vec<(Style, &str)>)],
+    line_numbers: bool,
+) -> String {
+    let background = theme.settings.background.unwrap_or(Color::WHITE);
+    let gutter = theme
+        .settings
+        .gutter_foreground
+        .or(theme.settings.foreground)
+        .unwrap_or(Color::BLACK);
+
+    let mut html = format!(
+        "<pre style=\"background-color:{};padding:0.5em;\">",
+        css_color(background)
+    );
+    for (line_number, ranges) in lines {
+        if line_numbers {
+            let _ = write!(
+                html,
+                "<span style=\"color:{};opacity:0.6;user-select:none;\">{:4} </span>",
+                css_color(gutter),
+                line_number
+            );
+        }
+        // Only spell out backgrounds that differ from the <pre> background
+        let line =
+            styled_line_to_highlighted_html(ranges, IncludeBackground::IfDifferent(background))
+                .unwrap_or_default();
+        html.push_str(&line);
+        if !ranges.last().is_some_and(|(_, text)| text.ends_with('\n')) {
+            html.push('\n');
+        }
+    }
+    html.push_str("</pre>\n");
+    html
+}
+
+// A theme color as a CSS hex color
+fn css_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+// Escape text for use in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
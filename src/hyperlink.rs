@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::terminal;
+
+// When to turn URLs into clickable OSC 8 hyperlinks
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HyperlinkChoice {
+    // Only when writing to a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl HyperlinkChoice {
+    // Whether links should be written, given whether output is going to a terminal
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            HyperlinkChoice::Always => true,
+            HyperlinkChoice::Never => false,
+            HyperlinkChoice::Auto => is_terminal,
+        }
+    }
+}
+
+// The OSC 8 sequence that makes the text after it (up to the next one) a link to `target`,
+// or ends the current link when `target` is empty
+pub fn osc8(target: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\", target)
+}
+
+// Wrap the http(s) URLs in `body`, a line that may already contain color escapes, in OSC 8
+// sequences. Highlighting can color parts of a URL differently, so the escapes inside one
+// are skipped over when finding where it ends, and kept in the output
+pub fn link_urls(body: &str) -> String {
+    let mut linked = String::with_capacity(body.len());
+    let mut index = 0;
+    while let Some(c) = body[index..].chars().next() {
+        let rest = &body[index..];
+        if c == '\x1b' {
+            let len = terminal::escape_len(rest);
+            linked.push_str(&rest[..len]);
+            index += len;
+            continue;
+        }
+        if rest.starts_with("https://") || rest.starts_with("http://") {
+            let (link, scanned) = scan_url(body, index);
+            match link {
+                Some((url, end)) => {
+                    linked.push_str(&osc8(&url));
+                    linked.push_str(&body[index..end]);
+                    linked.push_str(&osc8(""));
+                    index = end;
+                }
+                // Nothing after the scheme: the run goes out as it is, without being
+                // scanned again from each character in it
+                None => {
+                    linked.push_str(&body[index..scanned]);
+                    index = scanned;
+                }
+            }
+            continue;
+        }
+        linked.push(c);
+        index += c.len_utf8();
+    }
+    linked
+}
+
+// The run of URL characters that starts at `start` in `body` without any escape sequences
+// in it, and the offset in `body` where it ends, or None when there's nothing after the
+// scheme. The offset where the whole run ends, before any punctuation is left off the
+// end of the URL, comes with it
+fn scan_url(body: &str, start: usize) -> (Option<(String, usize)>, usize) {
+    let mut url = String::new();
+    // Where in `body` the URL would end after each of its characters
+    let mut ends = Vec::new();
+    let mut index = start;
+    while let Some(c) = body[index..].chars().next() {
+        if c == '\x1b' {
+            index += terminal::escape_len(&body[index..]);
+            continue;
+        }
+        if !is_url_char(c) {
+            break;
+        }
+        url.push(c);
+        index += c.len_utf8();
+        ends.push(index);
+    }
+    let scanned = index;
+    // Punctuation at the end more likely ends the sentence than the URL, and so does a
+    // closing parenthesis without an opening one in the URL, as in "(see https://a.b/c)"
+    while let Some(last) = url.chars().last()
+        && (matches!(last, '.' | ',' | ';' | ':' | '!' | '?' | '\'' | '"')
+            || (last == ')' && url.matches(')').count() > url.matches('(').count()))
+    {
+        url.pop();
+        ends.pop();
+    }
+    match ends.last() {
+        Some(&end) if !url.ends_with("://") => (Some((url, end)), scanned),
+        _ => (None, scanned),
+    }
+}
+
+// Characters that can be part of a URL in running text
+fn is_url_char(c: char) -> bool {
+    c.is_ascii_graphic() && !matches!(c, '<' | '>' | '"' | '`' | '{' | '}' | '|' | '\\' | '^')
+}
+
+// The target of links to lines of the file at `path`: `format` with "{path}" replaced by
+// the file's absolute path, leaving "{line}" to be filled in for each line. None when
+// `path` isn't a file on disk (e.g. a URL or an archive member)
+pub fn line_link_format(format: &str, path: &Path) -> Option<String> {
+    let path = fs::canonicalize(path).ok()?;
+    let mut path = path.to_string_lossy().replace('\\', "/");
+    // Windows paths like C:/src need a slash before them to stay paths in a URL; the
+    // verbatim prefix that canonicalize adds there isn't part of one
+    if let Some(stripped) = path.strip_prefix("//?/") {
+        path = stripped.to_string();
+    }
+    if !path.starts_with('/') {
+        path.insert(0, '/');
+    }
+    Some(format.replace("{path}", &percent_encode(&path)))
+}
+
+// Percent-encode the bytes of `path` that can't appear in a URL as they are
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str) -> String {
+        format!("{}{}{}", osc8(url), url, osc8(""))
+    }
+
+    #[test]
+    fn links_urls_in_text() {
+        assert_eq!(
+            link_urls("see https://a.b/c and http://d.e"),
+            format!("see {} and {}", link("https://a.b/c"), link("http://d.e"))
+        );
+    }
+
+    #[test]
+    fn leaves_trailing_punctuation_out() {
+        assert_eq!(
+            link_urls("(see https://a.b/c)."),
+            format!("(see {}).", link("https://a.b/c"))
+        );
+        assert_eq!(link_urls("https://a.b/f(x)"), link("https://a.b/f(x)"));
+    }
+
+    #[test]
+    fn keeps_escapes_inside_a_url() {
+        let body = "https://a.b/\x1b[31mc\x1b[0m d";
+        assert_eq!(
+            link_urls(body),
+            format!(
+                "{}https://a.b/\x1b[31mc{}\x1b[0m d",
+                osc8("https://a.b/c"),
+                osc8("")
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_text_without_urls_alone() {
+        for body in ["http", "https://", "hhh http:// here", "ahttpx"] {
+            assert_eq!(link_urls(body), body);
+        }
+    }
+
+    #[test]
+    fn handles_long_lines_of_almost_urls() {
+        // Every "h" in it used to start a scan through the rest of the line
+        let body = "h".repeat(100_000) + &"https://)".repeat(10_000);
+        assert_eq!(link_urls(&body), body);
+    }
+}
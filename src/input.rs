@@ -0,0 +1,388 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use memmap2::Mmap;
+
+use crate::archive;
+use crate::compression::Compression;
+use crate::error::CathError;
+use crate::net;
+use crate::preprocess::{Hook, Preprocessor};
+use crate::slice::ByteRange;
+
+// Files at least this large are memory-mapped instead of read through a buffer
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+// How inputs are opened and decoded
+pub struct InputOptions {
+    // Memory-map large files
+    pub mmap: bool,
+    // Detect inputs that aren't UTF-8 and transcode them
+    pub transcode: bool,
+    // The encoding given with --encoding, which skips detection
+    pub encoding: Option<&'static Encoding>,
+    // Show invalid UTF-8 as U+FFFD instead of failing
+    pub lossy: bool,
+    // Start files this many lines before their end, for --tail
+    pub tail: Option<usize>,
+    // Decompress compressed inputs
+    pub decompress: bool,
+    // The command (from CATH_OPEN or LESSOPEN) files are run through before being read
+    pub preprocessor: Option<Preprocessor>,
+    // The --preprocessor hooks, which take precedence over `preprocessor` for the files
+    // they match (the last matching one wins)
+    pub hooks: Vec<Hook>,
+    // The window of the text that's read, for --bytes
+    pub bytes: Option<ByteRange>,
+}
+
+// An opened input, ready to be read as UTF-8 text
+pub struct Input {
+    pub reader: Box<dyn BufRead>,
+    // The encoding named by the byte order mark the input started with, if any; the mark
+    // itself is never part of the text that's read
+    pub bom: Option<&'static Encoding>,
+    // Bytes of the input skipped before `reader` (a UTF-8 byte order mark, or everything
+    // before the last lines with --tail)
+    pub skipped: u64,
+    // Whether the input was opened at its last lines, as asked for with --tail
+    pub tailed: bool,
+    // The members of an archive given without a member path; `reader` then yields the
+    // listing as plain text
+    pub listing: Option<Vec<archive::Entry>>,
+    // The Content-Type a fetched URL was served with
+    pub content_type: Option<String>,
+    // Notices from opening the input, e.g. a preprocessor that failed on it
+    pub notices: Vec<CathError>,
+}
+
+// Read an entire input (a file, or stdin when `file_path` is None) into a String
+// Binary inputs can't be read as text, so they're skipped (with a notice handed to
+// `report`) and None is returned
+pub fn read_input(
+    file_path: Option<&Path>,
+    options: &InputOptions,
+    report: &mut dyn FnMut(CathError),
+) -> Result<Option<String>, CathError> {
+    let input = open_input(file_path, options)?;
+    input.notices.into_iter().for_each(&mut *report);
+    let mut reader = input.reader;
+    let read_error = |err| CathError::io(file_path, err);
+    if is_binary(&mut reader, options.lossy).map_err(read_error)? {
+        report(CathError::notice(file_path, "binary file not shown"));
+        return Ok(None);
+    }
+    let mut buffer = String::new();
+    read_to_string(&mut reader, &mut buffer, options.lossy).map_err(read_error)?;
+    Ok(Some(buffer))
+}
+
+// Open a file (or stdin when there's no path) for reading line by line, as UTF-8. Files of
+// at least MMAP_THRESHOLD bytes are memory-mapped when enabled, which saves copying them
+// through a read buffer, and compressed inputs and inputs in other encodings are
+// decompressed and transcoded on the fly. "archive.zip:path/inside" reads a single member
+// out of a zip or tar archive, and an archive on its own lists its members. http(s) URLs
+// are fetched, with the body streamed in as it arrives. Files a preprocessor has output
+// for are read as that output, and only the window of it asked for with --bytes is read
+pub fn open_input(file_path: Option<&Path>, options: &InputOptions) -> Result<Input, CathError> {
+    let read_error = |err| CathError::io(file_path, err);
+    let mut tail_offset = None;
+    let mut listing = None;
+    let mut content_type = None;
+    let mut notices = Vec::new();
+    let mut reader: Box<dyn BufRead> = match file_path {
+        None => Box::new(io::stdin().lock()),
+        // A member of an archive is read into memory, since neither zip nor tar members can
+        // be seeked into on their own. An archive on its own is read as its listing
+        Some(path) if net::is_url(path) => {
+            let (body, response_type) = net::fetch(&path.to_string_lossy()).map_err(read_error)?;
+            content_type = response_type;
+            body
+        }
+        Some(path) if let Some((archive, member)) = archive::split(path) => {
+            let data = match member {
+                Some(member) => archive::read_member(&archive, &member),
+                None => archive::entries(&archive).and_then(|entries| {
+                    let mut text = Vec::new();
+                    archive::write_listing(&mut text, &entries, false)?;
+                    listing = Some(entries);
+                    Ok(text)
+                }),
+            };
+            Box::new(io::Cursor::new(data.map_err(read_error)?))
+        }
+        Some(path)
+            if let Some(output) = match options.hooks.iter().rev().find(|hook| hook.matches(path)) {
+                Some(hook) => hook.run(path).map_err(|notice| notices.push(notice)).ok(),
+                None => options
+                    .preprocessor
+                    .as_ref()
+                    .and_then(|preprocessor| preprocessor.run(path)),
+            } =>
+        {
+            Box::new(io::Cursor::new(output))
+        }
+        Some(path) => {
+            let mut file = File::open(path).map_err(read_error)?;
+            let metadata = file.metadata().ok();
+            let is_file = metadata.as_ref().is_some_and(|metadata| metadata.is_file());
+            let is_large =
+                is_file && metadata.is_some_and(|metadata| metadata.len() >= MMAP_THRESHOLD);
+            if let Some(lines) = options.tail
+                && is_file
+                && can_seek_lines(&file, options).unwrap_or(false)
+            {
+                tail_offset = Some(find_tail(&mut file, lines).map_err(read_error)?);
+            }
+            let start = tail_offset.unwrap_or(0);
+            // SAFETY: the mapping is only read, and files that are expected to change while
+            // they're being read (--follow, --watch) are never mapped; see input_options
+            let map = if options.mmap && is_large {
+                unsafe { Mmap::map(&file) }.ok()
+            } else {
+                None
+            };
+            match map {
+                Some(map) => {
+                    let mut cursor = io::Cursor::new(map);
+                    cursor.set_position(start);
+                    Box::new(cursor)
+                }
+                None => {
+                    file.seek(SeekFrom::Start(start)).map_err(read_error)?;
+                    Box::new(BufReader::new(file))
+                }
+            }
+        }
+    };
+
+    if options.decompress
+        && let Some(compression) = reader.fill_buf().ok().and_then(Compression::detect)
+    {
+        reader = compression.decoder(reader).map_err(read_error)?;
+    }
+
+    // Byte order marks would confuse syntax detection and show up as garbage on the first
+    // line. A UTF-8 one is dropped here; UTF-16 ones are dropped by the transcoder
+    let bom = reader.fill_buf().ok().and_then(Encoding::for_bom);
+    let mut skipped = tail_offset.unwrap_or(0);
+    if let Some((encoding, len)) = bom
+        && encoding == encoding_rs::UTF_8
+    {
+        reader.consume(len);
+        skipped += len as u64;
+    }
+
+    let encoding = match options.encoding {
+        Some(encoding) => Some(encoding),
+        None if options.transcode => detect_encoding(&mut reader).unwrap_or(None),
+        None => None,
+    };
+    let reader: Box<dyn BufRead> = match encoding {
+        Some(encoding) => Box::new(BufReader::new(
+            DecodeReaderBytesBuilder::new()
+                .encoding(Some(encoding))
+                .build(reader),
+        )),
+        None => reader,
+    };
+    let reader = match options.bytes {
+        Some(bytes) => bytes.apply(reader).map_err(read_error)?,
+        None => reader,
+    };
+    Ok(Input {
+        reader,
+        bom: bom.map(|(encoding, _)| encoding),
+        skipped,
+        tailed: tail_offset.is_some(),
+        listing,
+        content_type,
+        notices,
+    })
+}
+
+// Whether lines of `file` can be found by looking for newline bytes, which is the case for
+// UTF-8 and the other encodings that keep ASCII as it is, but not for UTF-16 or compressed
+// files
+fn can_seek_lines(file: &File, options: &InputOptions) -> io::Result<bool> {
+    let mut reader = BufReader::new(file);
+    if options.decompress && Compression::detect(reader.fill_buf()?).is_some() {
+        return Ok(false);
+    }
+    let encoding = match options.encoding {
+        Some(encoding) => Some(encoding),
+        None if options.transcode => detect_encoding(&mut reader)?,
+        None => None,
+    };
+    Ok(encoding.is_none_or(|encoding| encoding.is_ascii_compatible()))
+}
+
+// The offset where the last `lines` lines of `file` start, found by reading it backwards a
+// block at a time, so only the end of a large file is ever looked at
+fn find_tail(file: &mut File, lines: usize) -> io::Result<u64> {
+    const BLOCK_SIZE: u64 = 64 * 1024;
+    let len = file.metadata()?.len();
+    if lines == 0 {
+        return Ok(len);
+    }
+    let mut buffer = vec![0; BLOCK_SIZE as usize];
+    let mut newlines = 0;
+    let mut end = len;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE);
+        let block = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(block)?;
+        for (index, _) in block
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|&(_, &byte)| byte == b'\n')
+        {
+            let line_start = start + index as u64 + 1;
+            // The newline ending the last line doesn't start another one
+            if line_start == len {
+                continue;
+            }
+            newlines += 1;
+            if newlines == lines {
+                return Ok(line_start);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+// Guess the encoding of an input from its first block, or None when it should be read as
+// UTF-8 (it is valid UTF-8, or looks like binary data that no encoding would fix)
+fn detect_encoding(reader: &mut dyn BufRead) -> io::Result<Option<&'static Encoding>> {
+    let block = reader.fill_buf()?;
+    // A UTF-16 byte order mark settles it (a UTF-8 one needs no transcoding)
+    if let Some((encoding, _)) = Encoding::for_bom(block)
+        && encoding != encoding_rs::UTF_8
+    {
+        return Ok(Some(encoding));
+    }
+    // Without a BOM, UTF-16 text from the ASCII range shows up as every other byte being
+    // NUL: the odd ones for little endian, the even ones for big endian
+    if block.len() >= 2 {
+        let zeros = |parity: usize| {
+            block
+                .iter()
+                .skip(parity)
+                .step_by(2)
+                .filter(|&&byte| byte == 0)
+                .count()
+        };
+        let pairs = block.len() / 2;
+        let (even, odd) = (zeros(0), zeros(1));
+        if odd * 10 >= pairs * 9 && even == 0 {
+            return Ok(Some(UTF_16LE));
+        }
+        if even * 10 >= pairs * 9 && odd == 0 {
+            return Ok(Some(UTF_16BE));
+        }
+    }
+    // Any other NUL means binary data, and valid UTF-8 needs nothing done (a multi-byte
+    // character cut off at the end of the block doesn't make it invalid)
+    if block.contains(&0) || is_utf8(block) {
+        return Ok(None);
+    }
+    // Legacy 8-bit and CJK encodings are guessed from their byte statistics
+    let mut detector = EncodingDetector::new();
+    detector.feed(block, false);
+    let guess = detector.guess(None, true);
+    Ok(Some(guess).filter(|&encoding| encoding != encoding_rs::UTF_8))
+}
+
+// Whether `block` is valid UTF-8, allowing a multi-byte character cut off at its end
+fn is_utf8(block: &[u8]) -> bool {
+    std::str::from_utf8(block).map_or_else(|err| err.error_len().is_none(), |_| true)
+}
+
+// Signatures of common binary formats, which can start out looking like text (a PDF's
+// header is plain ASCII, and the NULs in a PNG come after its signature)
+const BINARY_SIGNATURES: &[&[u8]] = &[
+    b"%PDF-",
+    b"\x89PNG\r\n",
+    b"GIF87a",
+    b"GIF89a",
+    b"\xff\xd8\xff",
+    b"PK\x03\x04",
+    b"\x7fELF",
+    b"\x1f\x8b",
+    b"\xfd7zXZ\0",
+    b"\x28\xb5\x2f\xfd",
+];
+
+// Whether the first block of `reader` looks like binary data: it starts with the signature
+// of a binary format, contains a NUL byte or isn't valid UTF-8 (unless `lossy` is set,
+// which allows broken text). Nothing is consumed, so the caller can still read the input
+// from the start
+pub fn is_binary(reader: &mut dyn BufRead, lossy: bool) -> io::Result<bool> {
+    let block = reader.fill_buf()?;
+    if block.contains(&0)
+        || BINARY_SIGNATURES
+            .iter()
+            .any(|signature| block.starts_with(signature))
+    {
+        return Ok(true);
+    }
+    Ok(!lossy && !is_utf8(block))
+}
+
+// Read the next line, including its newline, onto the end of `line` and return the number
+// of bytes consumed (0 at the end of the input). Invalid UTF-8 is an error, unless `lossy`
+// is set, in which case it becomes U+FFFD
+pub fn read_line(reader: &mut dyn BufRead, line: &mut String, lossy: bool) -> io::Result<usize> {
+    let mut bytes = Vec::new();
+    let read = reader.read_until(b'\n', &mut bytes)?;
+    line.push_str(&decode(bytes, lossy)?);
+    Ok(read)
+}
+
+// Read everything that's left in `reader` onto the end of `buffer`, decoding like read_line
+pub fn read_to_string(
+    reader: &mut dyn BufRead,
+    buffer: &mut String,
+    lossy: bool,
+) -> io::Result<usize> {
+    let mut bytes = Vec::new();
+    let read = reader.read_to_end(&mut bytes)?;
+    buffer.push_str(&decode(bytes, lossy)?);
+    Ok(read)
+}
+
+// Turn raw bytes into text, replacing invalid sequences when `lossy` is set
+pub fn decode(bytes: Vec<u8>, lossy: bool) -> io::Result<String> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(err) if lossy => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "input is not valid UTF-8 (use --lossy to show it anyway)",
+        )),
+    }
+}
+
+// Count the lines left in `reader`, including a last one without a newline
+pub fn count_lines(reader: &mut dyn BufRead) -> io::Result<usize> {
+    let mut count = 0;
+    let mut last = b'\n';
+    loop {
+        let block = reader.fill_buf()?;
+        let Some(&end) = block.last() else {
+            break;
+        };
+        count += block.iter().filter(|&&byte| byte == b'\n').count();
+        last = end;
+        let len = block.len();
+        reader.consume(len);
+    }
+    Ok(count + usize::from(last != b'\n'))
+}
@@ -0,0 +1,120 @@
+use syntect::highlighting::Style;
+
+// A piece of a line once nonprinting characters have been pulled out of it
+pub enum Piece<'a> {
+    // Ordinary text, keeping its highlighting style
+    Text(Style, &'a str),
+    // The visible stand-in for a nonprinting character, e.g. "^I" for a tab
+    Marker(String),
+}
+
+// Split highlighted ranges into text and markers for nonprinting characters, like cat -v -T:
+// control characters in caret notation (^X, ^? for DEL, M-^X for C1 controls), or only tabs
+// (as ^I) when `tabs_only` is set, like cat -T. Only the first `content_len` bytes are
+// looked at, so the line ending is left for --show-ends
+pub fn split_nonprinting<'a>(
+    ranges: &[(Style, &'a str)],
+    content_len: usize,
+    tabs_only: bool,
+) -> Vec<Piece<'a>> {
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+    for &(style, text) in ranges {
+        let mut start = 0;
+        for (index, c) in text.char_indices() {
+            if offset + index >= content_len {
+                break;
+            }
+            if tabs_only && c != '\t' {
+                continue;
+            }
+            if let Some(marker) = caret_notation(c) {
+                if start < index {
+                    pieces.push(Piece::Text(style, &text[start..index]));
+                }
+                pieces.push(Piece::Marker(marker));
+                start = index + c.len_utf8();
+            }
+        }
+        if start < text.len() {
+            pieces.push(Piece::Text(style, &text[start..]));
+        }
+        offset += text.len();
+    }
+    pieces
+}
+
+// The caret notation for a nonprinting character, or None for printable ones (which
+// includes everything outside the ASCII and C1 control ranges)
+fn caret_notation(c: char) -> Option<String> {
+    match c as u32 {
+        // ^@ through ^_, e.g. a tab is ^I and a carriage return ^M
+        code @ 0x00..=0x1f => Some(format!("^{}", char::from(code as u8 + 0x40))),
+        0x7f => Some("^?".to_string()),
+        // C1 controls are the high-bit versions, which cat shows with an M- prefix
+        code @ 0x80..=0x9f => Some(format!("M-^{}", char::from((code - 0x80) as u8 + 0x40))),
+        _ => None,
+    }
+}
+
+// Replace tabs with spaces up to the next multiple of `width` columns
+pub fn expand_tabs(line: &str, width: usize) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => {
+                let spaces = width - column % width;
+                expanded.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' | '\r' => {
+                expanded.push(c);
+                column = 0;
+            }
+            _ => {
+                expanded.push(c);
+                column += 1;
+            }
+        }
+    }
+    expanded
+}
+
+// Where each character of `line` starts in the text shown for it, counted in characters,
+// with one more entry for where the line ends, so ranges found in the line can be put on
+// what's shown. Tabs are expanded to `tab_width` columns when it's given, and with
+// `markers` set nonprinting characters are shown as markers instead (only tabs when
+// `tabs_only` is set), the way split_nonprinting does
+pub fn display_offsets(
+    line: &str,
+    tab_width: Option<usize>,
+    markers: bool,
+    tabs_only: bool,
+) -> Vec<usize> {
+    let content_len = line.trim_end_matches(['\n', '\r']).len();
+    let mut offsets = Vec::with_capacity(line.len() + 1);
+    let mut offset = 0;
+    let mut column = 0;
+    for (index, c) in line.char_indices() {
+        offsets.push(offset);
+        let width = match c {
+            _ if markers => match caret_notation(c) {
+                Some(marker) if index < content_len && (!tabs_only || c == '\t') => {
+                    marker.chars().count()
+                }
+                _ => 1,
+            },
+            '\t' => tab_width.map_or(1, |width| width - column % width),
+            _ => 1,
+        };
+        column = if matches!(c, '\n' | '\r') {
+            0
+        } else {
+            column + width
+        };
+        offset += width;
+    }
+    offsets.push(offset);
+    offsets
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+const INDENT: &str = "  ";
+
+// Whether `text` looks like it could be a JSON document, going by how it starts
+pub fn looks_like_json(text: &str) -> bool {
+    text.trim_start().starts_with(['{', '['])
+}
+
+// Whether an input is a stream of JSON lines, one JSON value per line: files by their
+// extension, and others (like stdin) when the first line in `head`, the start of the
+// input, is a JSON object or array of its own and the next line starts another one
+pub fn is_json_lines(file_path: Option<&Path>, head: &str) -> bool {
+    if let Some(ext) = file_path.and_then(Path::extension) {
+        return ["ndjson", "jsonl", "ldjson"]
+            .iter()
+            .any(|json_lines| ext.eq_ignore_ascii_case(json_lines));
+    }
+    let mut lines = head.lines();
+    let (Some(first), Some(second)) = (lines.next(), lines.next()) else {
+        return false;
+    };
+    looks_like_json(first)
+        && looks_like_json(second)
+        && serde_json::from_str::<Value>(first).is_ok()
+}
+
+// Spread a JSON document out over indented lines, with one member or element per line.
+// The text is reformatted as it is rather than parsed and written back, so keys stay in
+// their order and numbers are kept exactly as they were written. None if `text` isn't
+// valid JSON
+pub fn pretty(text: &str) -> Option<String> {
+    serde_json::from_str::<Value>(text).ok()?;
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut depth = 0;
+    let mut chars = text.chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&INDENT.repeat(depth));
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' | '[' => {
+                out.push(c);
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                // Empty objects and arrays stay on one line
+                if let Some(close) = chars.next_if(|&next| next == '}' || next == ']') {
+                    out.push(close);
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    out.push('\n');
+    Some(out)
+}
@@ -0,0 +1,1555 @@
+// cath's highlighting and printing. The command-line program is a thin wrapper around
+// `run`, and other tools can embed the same rendering through PrettyPrinter, or get
+// highlighted code as escapes, HTML or styled spans from render_ansi, render_html and
+// tokenize (which the ffi feature also makes callable from C, and wasm builds export
+// to JavaScript as highlight_to_html)
+mod archive;
+mod assets;
+mod clipboard;
+mod compression;
+mod config;
+mod detect;
+mod diff;
+mod error;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod follow;
+mod git;
+mod hexdump;
+mod highlight;
+mod html;
+mod hyperlink;
+mod input;
+mod invisibles;
+mod json;
+mod line_range;
+mod list;
+mod location;
+mod markdown;
+mod net;
+mod notebook;
+mod output;
+mod overstrike;
+#[cfg(not(target_arch = "wasm32"))]
+mod pager;
+mod pattern;
+#[cfg(feature = "image")]
+mod png;
+mod preprocess;
+mod pretty_printer;
+mod preview;
+mod printer;
+mod query;
+mod reformat;
+mod render;
+#[cfg(feature = "image")]
+mod sixel;
+mod slice;
+mod svg;
+mod table;
+mod terminal;
+mod tokens;
+mod walk;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+mod watch;
+mod xml;
+
+use assets::HighlightingAssets;
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use clipboard::{ClipboardCopy, CopyFormat};
+use detect::{SyntaxMapping, detect_syntax, find_syntax_by_language, find_syntax_by_path};
+use encoding_rs::Encoding;
+use hyperlink::HyperlinkChoice;
+use line_range::{Line, LineRange};
+use location::Location;
+use output::{OutputType, PagingMode};
+use preprocess::{Hook, Preprocessor};
+use preview::ImageProtocol;
+use printer::Decorations;
+use reformat::Format;
+use regex::Regex;
+use render::RenderMode;
+use slice::{ByteRange, ColumnRange};
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Highlighter, Style, Theme};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+use terminal::{AnsiFilter, ColorChoice, ColorDepth, TerminalWidth, WrapMode};
+
+pub use error::CathError;
+pub use highlight::{Span, render_ansi, render_html, tokenize};
+pub use pretty_printer::PrettyPrinter;
+// The types spans are styled with
+pub use syntect::highlighting::{Color, FontStyle};
+
+// Themes used when neither --theme nor CATH_THEME (nor the config file) picks one, for
+// terminals with a dark background and a light one
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+const DEFAULT_LIGHT_THEME: &str = "base16-ocean.light";
+// Lines longer than this (in bytes) are printed without highlighting unless
+// --strict-highlighting is given. syntect can't be interrupted partway through a line, and
+// its regexes can take minutes on a single huge line of minified JS or JSON, so the budget
+// is expressed as a length
+const MAX_HIGHLIGHT_LINE_LENGTH: usize = 16 * 1024;
+
+// Derive Parser trait to automatically parse command-line arguments
+#[derive(Parser, Clone)]
+// Set the program name to "cath"
+#[command(name = "cath", version)]
+// Set the program description that appears in help text
+#[command(about = "A simple cat-like utility with syntax highlighting", long_about = None)]
+// Let a subcommand such as `cath cache` take over the whole command line
+#[command(args_conflicts_with_subcommands = true)]
+// Let the last occurrence of an option win, so the command line overrides the config file
+#[command(args_override_self = true)]
+// Define the structure that holds our command-line arguments
+struct Args {
+    // Optional subcommand; when absent, cath prints the given files
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Input files to read
+    // Define a list of positional file paths; stdin is used when empty or for "-"
+    #[arg(
+        value_name = "FILE",
+        help = "Input files to read (reads from stdin when omitted or \"-\"); FILE:LINE or FILE:LINE:COLUMN, as compilers print them, shows the lines around that place (see --context)"
+    )]
+    file_paths: Vec<PathBuf>,
+
+    // Define a flag for plain output mode (short: -p, long: --plain)
+    #[arg(
+        short = 'p',
+        long = "plain",
+        help = "Output without syntax highlighting"
+    )]
+    plain: bool,
+
+    #[arg(
+        short = 'l',
+        short_alias = 'n',
+        long = "line-numbers",
+        help = "Show line numbers"
+    )]
+    line_numbers: bool,
+
+    #[arg(
+        short = 'b',
+        long = "number-nonblank",
+        help = "Number only non-empty lines, like cat -b (overrides --line-numbers)"
+    )]
+    number_nonblank: bool,
+
+    #[arg(
+        short = 'r',
+        long = "line-range",
+        value_name = "N:M",
+        value_parser = LineRange::parse,
+        allow_hyphen_values = true,
+        help = "Only print lines N to M (\":M\" from the start, \"N:\" to the end, \"N:+K\" for K more lines, negative numbers count from the end); can be repeated"
+    )]
+    line_ranges: Vec<LineRange>,
+
+    #[arg(
+        long = "head",
+        value_name = "N",
+        conflicts_with_all = ["line_ranges", "tail"],
+        help = "Only print the first N lines, like head -n"
+    )]
+    head: Option<usize>,
+
+    #[arg(
+        long = "tail",
+        value_name = "N",
+        conflicts_with = "line_ranges",
+        help = "Only print the last N lines, like tail -n; files are read from the end, so this is fast for large ones"
+    )]
+    tail: Option<usize>,
+
+    #[arg(
+        long = "columns",
+        value_name = "N:M",
+        value_parser = ColumnRange::parse,
+        help = "Only print columns N to M of each line (\":M\" from the start, \"N:\" to the end), e.g. for fixed-width data"
+    )]
+    columns: Option<ColumnRange>,
+
+    #[arg(
+        long = "bytes",
+        value_name = "OFFSET:LEN",
+        value_parser = ByteRange::parse,
+        conflicts_with_all = ["tail", "follow"],
+        help = "Only read LEN bytes of each input, starting OFFSET bytes in (\"OFFSET:\" to the end), e.g. for huge single-line records"
+    )]
+    bytes: Option<ByteRange>,
+
+    #[arg(
+        short = 'H',
+        long = "highlight-line",
+        value_name = "N[:M]",
+        value_parser = LineRange::parse,
+        allow_hyphen_values = true,
+        help = "Emphasize line N (or lines N to M) with the theme's line highlight color; can be repeated"
+    )]
+    highlight_lines: Vec<LineRange>,
+
+    #[arg(
+        short = 'm',
+        long = "pattern",
+        value_name = "REGEX",
+        value_parser = |pattern: &str| Regex::new(pattern),
+        help = "Make the matches of REGEX stand out on top of the syntax colors; can be repeated"
+    )]
+    patterns: Vec<Regex>,
+
+    #[arg(
+        long = "find",
+        value_name = "REGEX",
+        value_parser = |pattern: &str| Regex::new(pattern),
+        conflicts_with_all = ["line_ranges", "head", "tail", "follow"],
+        help = "Show the first line that matches REGEX in the middle of the lines around it (see --context), and report its line number"
+    )]
+    find: Option<Regex>,
+
+    #[arg(
+        long = "grep",
+        value_name = "REGEX",
+        value_parser = |pattern: &str| Regex::new(pattern),
+        conflicts_with_all = ["find", "follow"],
+        help = "Only show the lines that match REGEX, with the lines around them given by --context, --before-context and --after-context"
+    )]
+    grep: Option<Regex>,
+
+    #[arg(
+        short = 'v',
+        long = "invert-match",
+        requires = "grep",
+        help = "Show the lines that don't match --grep instead"
+    )]
+    invert_match: bool,
+
+    #[arg(
+        short = 'C',
+        long = "context",
+        value_name = "N",
+        help = "Number of lines to show on each side of the line found by --find [default: 5] or the lines matched by --grep [default: 0]"
+    )]
+    context: Option<usize>,
+
+    #[arg(
+        short = 'B',
+        long = "before-context",
+        value_name = "N",
+        help = "Number of lines to show before the lines matched by --grep"
+    )]
+    before_context: Option<usize>,
+
+    // -A is --show-all, as in cat
+    #[arg(
+        long = "after-context",
+        value_name = "N",
+        help = "Number of lines to show after the lines matched by --grep"
+    )]
+    after_context: Option<usize>,
+
+    #[arg(
+        short = 's',
+        long = "squeeze-blank",
+        help = "Collapse runs of empty lines into one, like cat -s"
+    )]
+    squeeze_blank: bool,
+
+    #[arg(
+        long = "squeeze-limit",
+        value_name = "N",
+        help = "Collapse runs of more than N empty lines into N (implies --squeeze-blank)"
+    )]
+    squeeze_limit: Option<usize>,
+
+    #[arg(
+        short = 'A',
+        long = "show-all",
+        help = "Show nonprinting characters like cat -A: ^X for control characters, ^I for tabs, ^? for DEL (implies --show-ends)"
+    )]
+    show_all: bool,
+
+    #[arg(short = 'T', long = "show-tabs", help = "Show tabs as ^I, like cat -T")]
+    show_tabs: bool,
+
+    #[arg(
+        long = "tabs",
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Expand tabs to the next multiple of N columns (ignored when tabs are shown)"
+    )]
+    tabs: Option<u16>,
+
+    #[arg(
+        short = 'E',
+        long = "show-ends",
+        help = "Mark line ends with $, and carriage returns (as in CRLF files) with ^M"
+    )]
+    show_ends: bool,
+
+    #[arg(
+        short = 'c',
+        long = "changes",
+        help = "Show git modification markers (+ added, ~ modified, - removed) in the gutter"
+    )]
+    changes: bool,
+
+    #[arg(
+        long = "diff",
+        help = "Only show lines changed compared to the git index, with some context"
+    )]
+    diff: bool,
+
+    #[arg(
+        long = "diff-context",
+        value_name = "N",
+        default_value_t = 2,
+        help = "Number of unchanged lines to show around each change with --diff"
+    )]
+    diff_context: usize,
+
+    #[arg(
+        long = "diff-files",
+        value_names = ["OLD", "NEW"],
+        num_args = 2,
+        conflicts_with_all = ["file_paths", "follow", "watch"],
+        help = "Show a highlighted diff between two files"
+    )]
+    diff_files: Option<Vec<PathBuf>>,
+
+    #[arg(
+        long = "side-by-side",
+        requires = "diff_files",
+        help = "Show --diff-files output in two columns instead of a unified diff"
+    )]
+    side_by_side: bool,
+
+    #[arg(
+        long = "blame",
+        help = "Prefix each line with the commit, author and date from git blame"
+    )]
+    blame: bool,
+
+    #[arg(
+        short = 'L',
+        long = "language",
+        value_name = "LANGUAGE",
+        help = "Force a syntax by name or file extension (e.g. \"rust\" or \"rs\"); MANPAGER=\"cath -pLman\" shows man pages with their bold and underlined text"
+    )]
+    language: Option<String>,
+
+    #[arg(
+        id = "theme",
+        long = "theme",
+        value_name = "NAME",
+        help = "Color theme to use for highlighting, whatever the terminal's background [env: CATH_THEME]"
+    )]
+    explicit_theme: Option<String>,
+
+    #[arg(
+        long = "theme-dark",
+        value_name = "NAME",
+        default_value = DEFAULT_THEME,
+        help = "Theme to use without --theme on a terminal with a dark background, or one that doesn't say [env: CATH_THEME_DARK]"
+    )]
+    theme_dark: String,
+
+    #[arg(
+        long = "theme-light",
+        value_name = "NAME",
+        default_value = DEFAULT_LIGHT_THEME,
+        help = "Theme to use without --theme on a terminal with a light background [env: CATH_THEME_LIGHT]"
+    )]
+    theme_light: String,
+
+    // The theme highlighting is done with, picked from the three above by settle_args
+    #[arg(skip)]
+    theme: String,
+
+    #[arg(
+        long = "list-themes",
+        help = "List all available themes with a preview of each"
+    )]
+    list_themes: bool,
+
+    #[arg(
+        long = "list-languages",
+        help = "List all supported languages and their file extensions"
+    )]
+    list_languages: bool,
+
+    #[arg(
+        long = "header",
+        help = "Print a header with the file name before each file"
+    )]
+    header: bool,
+
+    #[arg(
+        long = "style",
+        value_name = "COMPONENTS",
+        default_value = "snip",
+        value_parser = Decorations::parse,
+        help = "Decorations to draw: a comma-separated list of numbers, grid, header, header-size, header-modified, header-language, rule, snip and changes, or full or plain for all or none of them"
+    )]
+    style: Decorations,
+
+    #[arg(
+        long = "render",
+        value_name = "MODE",
+        value_enum,
+        default_value_t = RenderMode::Auto,
+        help = "Render documents instead of showing their source; auto renders Markdown files, Jupyter notebooks and CSV/TSV files on a terminal, markdown, notebook and table render any input as one"
+    )]
+    render: RenderMode,
+
+    #[arg(
+        long = "pretty-json",
+        help = "Reformat JSON inputs with indentation before highlighting them, so minified JSON is readable; inputs without a .json name are reformatted when their content is JSON"
+    )]
+    pretty_json: bool,
+
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        help = "Re-indent dense documents in FORMAT before highlighting them: json (like --pretty-json) or xml (XML and HTML, e.g. a document all on one line)"
+    )]
+    format: Option<Format>,
+
+    #[arg(
+        long = "query",
+        value_name = "FILTER",
+        value_parser = query::parse_query,
+        help = "Run a jq filter (e.g. '.items[].name') on JSON inputs and show its results instead of the input"
+    )]
+    query: Option<String>,
+
+    #[arg(
+        long = "delimiter",
+        value_name = "CHAR",
+        value_parser = table::parse_delimiter,
+        help = "Field delimiter of tables rendered from delimited values (default: tab for .tsv files, comma for .csv files, whichever the first line has more of otherwise)"
+    )]
+    delimiter: Option<u8>,
+
+    #[arg(
+        long = "file-name",
+        value_name = "NAME",
+        help = "Name to show in the header and detect the syntax by, for the inputs in order (e.g. stdin); can be repeated"
+    )]
+    file_names: Vec<PathBuf>,
+
+    #[arg(
+        long = "map-syntax",
+        value_name = "GLOB:LANGUAGE",
+        value_parser = SyntaxMapping::parse,
+        help = "Use LANGUAGE for files matching GLOB, e.g. '*.conf:INI' or '.env:sh'; can be repeated"
+    )]
+    map_syntax: Vec<SyntaxMapping>,
+
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "When to use colors; auto colors terminals unless NO_COLOR is set"
+    )]
+    color: ColorChoice,
+
+    #[arg(
+        short = 'f',
+        long = "force-colorization",
+        help = "Keep colors and decorations even when the output isn't a terminal"
+    )]
+    force_colorization: bool,
+
+    #[arg(
+        long = "hyperlinks",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = HyperlinkChoice::Auto,
+        help = "When to make URLs in the text clickable with OSC 8 hyperlinks; auto does on a terminal"
+    )]
+    hyperlinks: HyperlinkChoice,
+
+    #[arg(
+        long = "hyperlink-format",
+        value_name = "TEMPLATE",
+        default_value = "file://{path}#L{line}",
+        help = "Where line numbers link to with --hyperlinks, with {path} (absolute) and {line} filled in, e.g. 'vscode://file{path}:{line}'"
+    )]
+    hyperlink_format: String,
+
+    #[arg(
+        long = "image-protocol",
+        value_name = "PROTOCOL",
+        value_enum,
+        default_value_t = ImageProtocol::Auto,
+        help = "How to show PNG, JPEG and GIF images inline on a terminal; auto picks one the terminal supports, never shows a hex dump"
+    )]
+    image_protocol: ImageProtocol,
+
+    #[arg(
+        long = "color-depth",
+        value_name = "DEPTH",
+        value_enum,
+        default_value_t = ColorDepth::Auto,
+        help = "Colors the terminal supports; auto guesses from COLORTERM and TERM"
+    )]
+    color_depth: ColorDepth,
+
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        conflicts_with_all = ["follow", "watch", "to_png"],
+        help = "Write the output to FILE instead of stdout; colors follow --color"
+    )]
+    output: Option<PathBuf>,
+
+    #[arg(
+        long = "force",
+        requires = "output",
+        help = "Overwrite the --output file if it exists"
+    )]
+    force: bool,
+
+    #[arg(
+        long = "wrap",
+        value_name = "MODE",
+        value_enum,
+        default_value_t = WrapMode::Auto,
+        help = "Soft-wrap lines wider than the terminal, indented past the gutter; auto wraps on a terminal or with --terminal-width"
+    )]
+    wrap: WrapMode,
+
+    #[arg(
+        short = 'S',
+        long = "chop-long-lines",
+        conflicts_with = "wrap",
+        help = "Cut lines wider than the terminal off at its edge, with a … marking where, instead of letting them wrap (also passes -S to less)"
+    )]
+    chop_long_lines: bool,
+
+    #[arg(
+        long = "terminal-width",
+        value_name = "WIDTH",
+        value_parser = TerminalWidth::parse,
+        allow_hyphen_values = true,
+        help = "Lay out output for WIDTH columns (or +N/-N columns relative to the terminal), also when piped"
+    )]
+    terminal_width: Option<TerminalWidth>,
+
+    #[arg(
+        long = "paging",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = PagingMode::Auto,
+        help = "When to use a pager (CATH_PAGER or PAGER, defaulting to less; \"builtin\" selects the internal pager)"
+    )]
+    paging: PagingMode,
+
+    #[arg(
+        short = 'F',
+        long = "follow",
+        help = "Keep the file open and print lines as they are appended, like tail -f"
+    )]
+    follow: bool,
+
+    #[arg(
+        long = "watch",
+        conflicts_with = "follow",
+        help = "Re-render the files whenever they change"
+    )]
+    watch: bool,
+
+    #[arg(
+        long = "copy",
+        value_name = "FORMAT",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "ansi",
+        conflicts_with_all = ["follow", "watch"],
+        help = "Also copy the output to the clipboard (through the terminal, with OSC 52); FORMAT plain leaves out the colors"
+    )]
+    copy: Option<CopyFormat>,
+
+    #[arg(
+        short = 'R',
+        long = "recursive",
+        help = "Print the text files inside directories, each with a header"
+    )]
+    recursive: bool,
+
+    #[arg(
+        long = "max-depth",
+        value_name = "N",
+        requires = "recursive",
+        help = "Only go N levels deep into directories with --recursive (1 is just the files directly inside them)"
+    )]
+    max_depth: Option<usize>,
+
+    #[arg(
+        long = "hidden",
+        requires = "recursive",
+        help = "Include hidden files and directories with --recursive"
+    )]
+    hidden: bool,
+
+    #[arg(
+        long = "no-ignore",
+        requires = "recursive",
+        help = "Include files excluded by .gitignore, .ignore and the like with --recursive"
+    )]
+    no_ignore: bool,
+
+    #[arg(
+        long = "include",
+        value_name = "GLOB",
+        requires = "recursive",
+        help = "Only print the files matching GLOB with --recursive, e.g. '*.rs'; can be repeated"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        requires = "recursive",
+        help = "Leave out the files and directories matching GLOB with --recursive, e.g. 'target/*'; can be repeated"
+    )]
+    exclude: Vec<String>,
+
+    #[arg(
+        long = "encoding",
+        value_name = "ENCODING",
+        value_parser = parse_encoding,
+        help = "Read inputs in ENCODING (e.g. utf-16le, latin1, shift_jis) instead of detecting it"
+    )]
+    encoding: Option<&'static Encoding>,
+
+    #[arg(
+        long = "lossy",
+        help = "Show invalid UTF-8 as U+FFFD instead of failing (text with a few bad bytes isn't treated as binary)"
+    )]
+    lossy: bool,
+
+    #[arg(
+        long = "show-bom",
+        help = "Mention a byte order mark in the file's --header line (it's never printed)"
+    )]
+    show_bom: bool,
+
+    #[arg(
+        long = "hex",
+        conflicts_with_all = ["to_html", "to_svg", "to_png"],
+        help = "Show a hex dump instead of text (binary files always get one)"
+    )]
+    hex: bool,
+
+    #[arg(
+        long = "strict-highlighting",
+        help = "Highlight every line, however long, instead of printing very long lines plain"
+    )]
+    strict_highlighting: bool,
+
+    #[arg(
+        long = "max-highlight-size",
+        value_name = "SIZE",
+        default_value = "10M",
+        value_parser = parse_size,
+        help = "Print files larger than SIZE (in bytes, or with a K, M or G suffix) without highlighting"
+    )]
+    max_highlight_size: u64,
+
+    #[arg(
+        long = "no-mmap",
+        help = "Read large files normally instead of memory-mapping them (e.g. on network file systems)"
+    )]
+    no_mmap: bool,
+
+    #[arg(
+        long = "lessopen",
+        help = "Run files through less's input preprocessor (LESSOPEN and LESSCLOSE) when CATH_OPEN doesn't set one, e.g. to read PDFs as text with lesspipe"
+    )]
+    lessopen: bool,
+
+    #[arg(
+        long = "preprocessor",
+        value_name = "GLOB:COMMAND",
+        value_parser = Hook::parse,
+        help = "Read files matching GLOB as the output of COMMAND, which gets the file's path at %s (or at the end), e.g. '*.plist:plutil -p' or '*.sqlite:sqlite3 %s .dump'; can be repeated, and set in the config file's [preprocessors] section"
+    )]
+    preprocessors: Vec<Hook>,
+
+    #[arg(
+        long = "to-html",
+        help = "Write a standalone HTML document instead of terminal output"
+    )]
+    to_html: bool,
+
+    #[arg(
+        long = "html-fragment",
+        requires = "to_html",
+        help = "With --to-html, only write the <pre> blocks, without the surrounding document"
+    )]
+    html_fragment: bool,
+
+    #[arg(
+        long = "to-svg",
+        conflicts_with = "to_html",
+        help = "Write the highlighted output as an SVG image instead of terminal output"
+    )]
+    to_svg: bool,
+
+    #[arg(
+        long = "svg-font",
+        value_name = "FAMILY",
+        default_value = "monospace",
+        help = "Font family used in SVG output"
+    )]
+    svg_font: String,
+
+    #[arg(
+        long = "svg-font-size",
+        value_name = "PIXELS",
+        default_value_t = 14.0,
+        help = "Font size used in SVG output"
+    )]
+    svg_font_size: f32,
+
+    #[arg(
+        long = "svg-padding",
+        value_name = "PIXELS",
+        default_value_t = 16.0,
+        help = "Padding around the code in SVG output"
+    )]
+    svg_padding: f32,
+
+    #[arg(
+        long = "svg-background",
+        value_name = "COLOR",
+        value_parser = svg::parse_color,
+        help = "Background color (#rrggbb) for SVG output, instead of the theme's"
+    )]
+    svg_background: Option<syntect::highlighting::Color>,
+
+    #[arg(
+        long = "to-png",
+        value_name = "PATH",
+        conflicts_with_all = ["to_html", "to_svg"],
+        help = "Save the highlighted output as a PNG screenshot (needs the `image` feature)"
+    )]
+    to_png: Option<PathBuf>,
+
+    #[arg(
+        long = "tokens",
+        conflicts_with_all = ["to_html", "to_svg", "to_png", "hex"],
+        help = "Write the highlighted spans of each line as JSON, one object per line, for editors and other tools (offsets are in bytes)"
+    )]
+    tokens: bool,
+
+    #[arg(
+        long = "png-font",
+        value_name = "PATH",
+        help = "TrueType/OpenType font used for PNG output (defaults to a system monospace font)"
+    )]
+    png_font: Option<PathBuf>,
+
+    #[arg(
+        long = "png-font-size",
+        value_name = "PIXELS",
+        default_value_t = 16.0,
+        help = "Font size used in PNG output"
+    )]
+    png_font_size: f32,
+
+    // Hidden since it's meant for packagers rather than everyday use
+    #[arg(
+        long = "generate-man",
+        hide = true,
+        help = "Print a roff man page to stdout"
+    )]
+    generate_man: bool,
+
+    // Only declared so clap accepts it; the path is read before parsing in config.rs
+    #[arg(
+        long = "config-file",
+        value_name = "PATH",
+        help = "Read default options from PATH instead of ~/.config/cath/config"
+    )]
+    config_file: Option<PathBuf>,
+
+    // The places "file:line:column" arguments point at, for the files they name; filled in
+    // after parsing
+    #[arg(skip)]
+    locations: Vec<(PathBuf, Location)>,
+
+    // Whether any of the files is an image, as settle_args finds
+    #[arg(skip)]
+    has_images: bool,
+}
+
+// Subcommands that run instead of printing files
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Manage the binary cache of compiled syntaxes and themes
+    Cache(CacheArgs),
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_name = "SHELL", help = "Shell to generate completions for")]
+        shell: Shell,
+    },
+}
+
+// Arguments for `cath cache`: exactly one action must be chosen
+#[derive(clap::Args, Clone)]
+#[group(required = true, multiple = false)]
+struct CacheArgs {
+    #[arg(
+        short = 'b',
+        long = "build",
+        help = "Compile syntaxes and themes (including custom ones) into the cache"
+    )]
+    build: bool,
+
+    #[arg(short = 'c', long = "clear", help = "Remove the cache")]
+    clear: bool,
+}
+
+// Run cath with the command line it was started with, as the cath binary does
+pub fn run() {
+    output::reset_sigpipe();
+    // Parse command-line arguments, with options from the config file and environment
+    // prepended, into our Args struct
+    let command = Args::command();
+    let subcommands: Vec<&str> = command.get_subcommands().map(|c| c.get_name()).collect();
+    let mut args = Args::parse_from(config::merged_args(&subcommands));
+    // Output to a file counts as piped, like a shell redirection would
+    let is_terminal = args.output.is_none() && io::stdout().is_terminal();
+    let colored = settle_args(&mut args, is_terminal);
+
+    // Subcommands run instead of printing anything, so handle them first
+    match &args.command {
+        Some(Command::Cache(cache_args)) => {
+            run_cache(cache_args);
+            return;
+        }
+        Some(Command::Completions { shell }) => {
+            print_completions(*shell);
+            return;
+        }
+        None => {}
+    }
+    if args.generate_man {
+        clap_mangen::Man::new(Args::command())
+            .render(&mut io::stdout())
+            .unwrap_or_else(|err| output::write_failed(err));
+        return;
+    }
+
+    // Load the color themes, including the user's custom themes. Syntax definitions are
+    // only loaded when something is going to be highlighted (or listed, or named in a
+    // header): plain output gets by with just the plain text syntax, which saves
+    // deserializing all of them
+    let assets = HighlightingAssets::load();
+    let needs_syntaxes = !args.plain
+        || args.list_themes
+        || args.list_languages
+        || args.language.is_some()
+        || args.style.header_language;
+    let plain_text_set;
+    let ps = if needs_syntaxes {
+        assets.syntax_set()
+    } else {
+        plain_text_set = assets::plain_text_syntax_set();
+        &plain_text_set
+    };
+    let ts = &assets.theme_set;
+
+    // Listing themes or languages is a standalone mode that doesn't read any input
+    if args.list_themes {
+        let mut output = OutputType::from_mode(args.paging, args.chop_long_lines);
+        list::list_themes(output.handle(), ps, ts, args.color_depth)
+            .unwrap_or_else(|err| output::exit_failed(err));
+        return;
+    }
+    if args.list_languages {
+        let mut output = OutputType::from_mode(args.paging, args.chop_long_lines);
+        list::list_languages(output.handle(), ps).unwrap_or_else(|err| output::write_failed(err));
+        return;
+    }
+
+    // Look up the requested theme, listing the valid names if it doesn't exist
+    let theme = ts.themes.get(&args.theme).unwrap_or_else(|| {
+        eprintln!("cath: {}", CathError::UnknownTheme(args.theme.clone()));
+        eprintln!("Available themes:");
+        for name in ts.themes.keys() {
+            eprintln!("  {}", name);
+        }
+        process::exit(1);
+    });
+
+    // Resolve a forced syntax up front so a typo is reported once, before any output
+    let forced_syntax = args.language.as_deref().map(|language| {
+        find_syntax_by_language(ps, language).unwrap_or_else(|| {
+            eprintln!("cath: {}", CathError::UnknownLanguage(language.to_string()));
+            process::exit(1);
+        })
+    });
+
+    // The same goes for the languages in --map-syntax rules (which can only be looked up
+    // when the syntaxes are loaded, and don't matter otherwise)
+    if needs_syntaxes
+        && let Some(mapping) = args
+            .map_syntax
+            .iter()
+            .find(|mapping| find_syntax_by_language(ps, &mapping.language).is_none())
+    {
+        eprintln!(
+            "cath: unknown language '{}' in --map-syntax (use a syntax name or file extension)",
+            mapping.language
+        );
+        process::exit(1);
+    }
+
+    // Following and watching never end, so they can't go through a pager
+    if args.follow && args.file_paths.len() != 1 {
+        eprintln!("cath: --follow needs exactly one file");
+        process::exit(1);
+    }
+    if args.watch && (args.file_paths.is_empty() || args.file_paths.iter().any(|p| p == "-")) {
+        eprintln!("cath: --watch needs files to watch and can't read from stdin");
+        process::exit(1);
+    }
+    // Exports are meant for files and other programs, not for reading in a pager, and a
+    // pager would mangle inline images
+    let shows_images = args.image_protocol != ImageProtocol::Never && args.has_images;
+    let paging = if args.follow
+        || args.watch
+        || args.to_html
+        || args.to_svg
+        || args.to_png.is_some()
+        || args.tokens
+        || shows_images
+    {
+        PagingMode::Never
+    } else {
+        args.paging
+    };
+
+    // Get the output destination: the --output file, stdout, or a pager when paging is enabled
+    let mut output = match &args.output {
+        Some(path) => OutputType::to_file(path, args.force).unwrap_or_else(|err| {
+            eprintln!("cath: {}", err);
+            process::exit(1);
+        }),
+        None => OutputType::from_mode(paging, args.chop_long_lines),
+    };
+    // Wrap it in a BufWriter for better performance (batches writes instead of flushing each time)
+    // With --copy, what comes out is also kept for the clipboard. Links asked for are kept
+    // in uncolored output too, since that's mostly what --hyperlinks=always is for
+    let mut handle = BufWriter::new(
+        AnsiFilter::new(ClipboardCopy::new(output.handle(), args.copy), !colored)
+            .keep_hyperlinks(args.hyperlinks == HyperlinkChoice::Always),
+    );
+
+    // Comparing two files replaces the normal output entirely
+    if let Some(diff_files) = &args.diff_files {
+        if let Err(err) = print_diff_files(&mut handle, &args, diff_files, ps, theme, forced_syntax)
+        {
+            if err.is_broken_pipe() {
+                process::exit(0);
+            }
+            eprintln!("cath: {}", err);
+            process::exit(1);
+        }
+        handle
+            .flush()
+            .unwrap_or_else(|err| output::write_failed(err));
+        copy_output(&mut handle);
+        return;
+    }
+
+    // An input that can't be read is reported when its turn comes, and the others are
+    // printed all the same; the exit status then says that some of them failed (notices
+    // are only printed). The output being closed ends it all, quietly
+    let mut failed = false;
+    let mut report = |err: CathError| {
+        if err.is_broken_pipe() {
+            process::exit(0);
+        }
+        eprintln!("cath: {}", err);
+        failed |= !err.is_notice();
+    };
+
+    // With no file arguments, read a single input from stdin; with --recursive,
+    // directories stand for the files in them
+    let inputs = if args.file_paths.is_empty() {
+        vec![PathBuf::from("-")]
+    } else if args.recursive {
+        let options = walk::WalkOptions {
+            max_depth: args.max_depth,
+            hidden: args.hidden,
+            no_ignore: args.no_ignore,
+            include: args.include.clone(),
+            exclude: args.exclude.clone(),
+        };
+        walk::expand_directories(&args.file_paths, &options, &mut report)
+            .unwrap_or_else(|err| output::exit_failed(err))
+    } else {
+        args.file_paths.clone()
+    };
+
+    // PNG export writes straight to its own file
+    if let Some(path) = &args.to_png {
+        save_png(path, &args, &inputs, ps, theme, forced_syntax, &mut report);
+        if failed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // An SVG image needs all lines up front to know its size, so it's rendered separately
+    if args.to_svg {
+        let lines =
+            collect_highlighted_lines(&args, &inputs, ps, theme, forced_syntax, &mut report);
+        let options = svg::SvgOptions {
+            font_family: args.svg_font.clone(),
+            font_size: args.svg_font_size,
+            padding: args.svg_padding,
+            background: args.svg_background,
+            line_numbers: args.style.numbers,
+        };
+        write!(handle, "{}", svg::render_svg(theme, &lines, &options))
+            .unwrap_or_else(|err| output::write_failed(err));
+        handle
+            .flush()
+            .unwrap_or_else(|err| output::write_failed(err));
+        copy_output(&mut handle);
+        if failed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // A standalone HTML document wraps the output of all inputs
+    let html_document = args.to_html && !args.html_fragment;
+    if html_document {
+        let title = inputs
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(handle, "{}", html::document_start(theme, &title))
+            .unwrap_or_else(|err| output::write_failed(err));
+    }
+
+    if args.watch {
+        // Clear the screen and redraw everything from the top on every change
+        let watched = watch::watch(&inputs, || {
+            write!(handle, "\x1b[2J\x1b[H")?;
+            printer::print_inputs(
+                &mut handle,
+                &args,
+                &inputs,
+                ps,
+                theme,
+                forced_syntax,
+                &mut report,
+            );
+            handle.flush()
+        });
+        if let Err(err) = watched {
+            report(CathError::Watch(err));
+        }
+    } else {
+        printer::print_inputs(
+            &mut handle,
+            &args,
+            &inputs,
+            ps,
+            theme,
+            forced_syntax,
+            &mut report,
+        );
+    }
+    if html_document {
+        write!(handle, "{}", html::document_end()).unwrap_or_else(|err| output::write_failed(err));
+    }
+    // Flush before the pager (if any) is waited on when `output` goes out of scope
+    handle
+        .flush()
+        .unwrap_or_else(|err| output::write_failed(err));
+    copy_output(&mut handle);
+    if failed {
+        // Exiting skips destructors, so the pager is waited on here instead
+        drop(handle);
+        drop(output);
+        process::exit(1);
+    }
+}
+
+// Settle the options that follow from other options, and from whether the output goes to
+// a terminal, once they've been parsed. Returns whether the output is colored
+fn settle_args(args: &mut Args, is_terminal: bool) -> bool {
+    // cmd.exe and PowerShell pass wildcards through as they are, so expand them here
+    if cfg!(windows) {
+        args.file_paths = expand_wildcards(std::mem::take(&mut args.file_paths));
+    }
+    // "src/main.rs:87:5", as compilers and grep print it, opens the file at that line
+    for path in &mut args.file_paths {
+        if let Some((file, location)) = location::split(path) {
+            *path = file.clone();
+            args.locations.push((file, location));
+        }
+    }
+    // Settle the color depth once, so every line is rendered the same way
+    args.color_depth = args.color_depth.resolve();
+    if args.show_all {
+        args.show_ends = true;
+    }
+    // The flags for single decorations are shorthands for their --style components
+    args.style.numbers |= args.line_numbers;
+    args.style.header |= args.header;
+    args.style.changes |= args.changes;
+    // Files found by --recursive need their names shown to make sense of the output
+    args.style.header |= args.recursive;
+    // --head is just the range of lines it starts with
+    if let Some(lines) = args.head {
+        args.line_ranges.push(LineRange {
+            start: Line::Start(1),
+            end: Line::Start(lines),
+        });
+    }
+    // The match --find shows, and the ones --grep does, stand out like --pattern matches
+    if let Some(pattern) = &args.find {
+        args.patterns.push(pattern.clone());
+    }
+    if let Some(pattern) = args.grep.as_ref().filter(|_| !args.invert_match) {
+        args.patterns.push(pattern.clone());
+    }
+    // Without colors there's no point in highlighting; escapes still used by the
+    // decorations are filtered out of the output below. Exports carry their colors in
+    // the document itself, so they're highlighted either way
+    let exporting = args.to_html || args.to_svg || args.to_png.is_some() || args.tokens;
+    // A console that can't be made to take escapes would show them as text, so it gets
+    // the output without colors, unless --color=always asks for them regardless
+    if is_terminal && args.color == ColorChoice::Auto && !terminal::enable_ansi() {
+        args.color = ColorChoice::Never;
+    }
+    if args.force_colorization && args.color == ColorChoice::Auto {
+        args.color = ColorChoice::Always;
+    }
+    let colored = exporting || args.color.enabled(is_terminal);
+    // Without a --theme, the theme goes with the terminal's background. That's only asked
+    // about when highlighted text is going to the terminal, since asking takes a round trip
+    args.theme = match &args.explicit_theme {
+        Some(theme) => theme.clone(),
+        None if colored
+            && is_terminal
+            && !exporting
+            && !args.plain
+            && terminal::background_is_light() == Some(true) =>
+        {
+            args.theme_light.clone()
+        }
+        None => args.theme_dark.clone(),
+    };
+    // Asking the terminal whether it does sixels takes a round trip, so that's only done
+    // when there are images to show
+    args.has_images = args
+        .file_paths
+        .iter()
+        .any(|path| preview::is_image_file(path));
+    args.image_protocol = args.image_protocol.resolve(is_terminal, args.has_images);
+    // --tokens describes the highlighting alone, so there's nothing to draw around it
+    if args.tokens {
+        args.image_protocol = ImageProtocol::Never;
+        args.style = Decorations::default();
+    }
+    // From here on, Always means links are written and Never that they aren't
+    args.hyperlinks = if args.hyperlinks.enabled(is_terminal) {
+        HyperlinkChoice::Always
+    } else {
+        HyperlinkChoice::Never
+    };
+    // An explicit width only makes sense if the output is laid out for it
+    if args.wrap == WrapMode::Auto
+        && ((!is_terminal && args.terminal_width.is_none()) || args.chop_long_lines)
+    {
+        args.wrap = WrapMode::Never;
+    }
+    // Piped output is meant for other programs, so drop the gutter and headers unless
+    // asked to keep them (e.g. for fzf previews). --number-nonblank stays, since scripts
+    // written for cat -b ask for exactly that output
+    if !args.force_colorization && !exporting && !is_terminal {
+        args.style.numbers = false;
+        args.style.header = false;
+        args.style.changes = false;
+        args.style.grid = false;
+        args.style.rule = false;
+        args.blame = false;
+    }
+    if !colored {
+        args.plain = true;
+    }
+    colored
+}
+
+// Put the output on the clipboard for --copy, now that all of it has been written
+fn copy_output(handle: &mut BufWriter<AnsiFilter<ClipboardCopy<impl Write>>>) {
+    if let Err(err) = handle.get_mut().get_mut().copy() {
+        eprintln!("cath: failed to copy to the clipboard: {}", err);
+    }
+}
+
+// Print a highlighted diff between the two files given to --diff-files
+fn print_diff_files(
+    handle: &mut impl Write,
+    args: &Args,
+    diff_files: &[PathBuf],
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+) -> Result<(), CathError> {
+    let [old_path, new_path] = diff_files else {
+        unreachable!("clap enforces exactly two paths");
+    };
+    let read =
+        |path: &PathBuf| fs::read_to_string(path).map_err(|err| CathError::io(Some(path), err));
+    let old_text = read(old_path)?;
+    let new_text = read(new_path)?;
+
+    // Each side is highlighted with its own syntax, so renames across languages still work
+    let side = |path: &PathBuf, text| {
+        let syntax = if args.plain || too_large_to_highlight(args, Some(path)) {
+            ps.find_syntax_plain_text()
+        } else {
+            forced_syntax
+                .unwrap_or_else(|| detect_syntax(ps, Some(path), text, None, &args.map_syntax))
+        };
+        diff::DiffSide::new(path, text, syntax, ps, theme)
+    };
+    let old = side(old_path, &old_text)?;
+    let new = side(new_path, &new_text)?;
+
+    // Side-by-side output splits the terminal width between the two files
+    let width = terminal::width(args.terminal_width);
+    let layout = diff::DiffLayout {
+        side_by_side: args.side_by_side,
+        width,
+        color_depth: args.color_depth,
+    };
+    diff::print_diff(handle, &old_text, &new_text, &old, &new, &layout).map_err(CathError::Output)
+}
+
+// Render all inputs into a PNG screenshot at `path`
+#[cfg(feature = "image")]
+fn save_png(
+    path: &Path,
+    args: &Args,
+    inputs: &[PathBuf],
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+    report: &mut dyn FnMut(CathError),
+) {
+    let lines = collect_highlighted_lines(args, inputs, ps, theme, forced_syntax, report);
+    let options = png::PngOptions {
+        font_path: args.png_font.clone(),
+        font_size: args.png_font_size,
+        line_numbers: args.style.numbers,
+    };
+    if let Err(err) = png::save_png(path, theme, &lines, &options) {
+        eprintln!("cath: {}", err);
+        process::exit(1);
+    }
+}
+
+// Without the `image` feature there is no rasterizer, so explain how to get one
+#[cfg(not(feature = "image"))]
+fn save_png(
+    _path: &Path,
+    _args: &Args,
+    _inputs: &[PathBuf],
+    _ps: &SyntaxSet,
+    _theme: &Theme,
+    _forced_syntax: Option<&SyntaxReference>,
+    _report: &mut dyn FnMut(CathError),
+) {
+    eprintln!("cath: --to-png needs cath to be built with `--features image`");
+    process::exit(1);
+}
+
+// Read and highlight all inputs, returning their requested lines one after another with
+// owned text, for exports that lay out the whole output at once. Inputs that fail are
+// handed to `report` and left out, and so are notices about them
+fn collect_highlighted_lines(
+    args: &Args,
+    inputs: &[PathBuf],
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+    report: &mut dyn FnMut(CathError),
+) -> Vec<(usize, Vec<(Style, String)>)> {
+    let mut all_lines = Vec::new();
+    for (index, input) in inputs.iter().enumerate() {
+        let file_path = Some(input.as_path()).filter(|path| *path != Path::new("-"));
+        let content = match input::read_input(file_path, &input_options(args), report) {
+            Ok(Some(content)) => content,
+            Ok(None) => continue,
+            Err(err) => {
+                report(err);
+                continue;
+            }
+        };
+        let syntax = if args.plain || too_large_to_highlight(args, file_path) {
+            ps.find_syntax_plain_text()
+        } else {
+            forced_syntax
+                .or_else(|| syntax_for_file_name(args, index, ps))
+                .unwrap_or_else(|| detect_syntax(ps, file_path, &content, None, &args.map_syntax))
+        };
+        let line_ranges = line_range::resolve(
+            &requested_ranges(args),
+            LinesWithEndings::from(&content).count(),
+        );
+        let lines = match highlight_lines(
+            &content,
+            file_path,
+            syntax,
+            ps,
+            theme,
+            &line_ranges,
+            args.strict_highlighting,
+        ) {
+            Ok(lines) => lines,
+            Err(err) => {
+                report(err);
+                continue;
+            }
+        };
+        for (line_number, ranges) in lines {
+            let ranges = ranges
+                .into_iter()
+                .map(|(style, text)| (style, text.to_string()))
+                .collect();
+            all_lines.push((line_number, ranges));
+        }
+    }
+    all_lines
+}
+
+// Build or clear the asset cache, exiting with an error message on failure
+fn run_cache(cache_args: &CacheArgs) {
+    let (result, action) = if cache_args.build {
+        (assets::build_cache(), "Wrote")
+    } else {
+        (assets::clear_cache(), "Cleared")
+    };
+    match result {
+        Ok(dir) => println!("{} asset cache in {}", action, dir.display()),
+        Err(err) => {
+            eprintln!("cath: failed to update the asset cache: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+// Write a completion script for `shell` to stdout
+// The installed theme and language names are baked into the script as possible values,
+// so shells that complete option values can offer them
+fn print_completions(shell: Shell) {
+    let assets = HighlightingAssets::load();
+    let themes: Vec<String> = assets.theme_set.themes.keys().cloned().collect();
+    let languages: Vec<String> = assets
+        .syntax_set()
+        .syntaxes()
+        .iter()
+        .filter(|syntax| !syntax.hidden)
+        .map(|syntax| syntax.name.clone())
+        .collect();
+
+    let mut command = Args::command();
+    for id in ["theme", "theme_dark", "theme_light"] {
+        command = command.mut_arg(id, |arg| {
+            arg.value_parser(PossibleValuesParser::new(&themes))
+                .hide_possible_values(true)
+        });
+    }
+    let mut command = command.mut_arg("language", |arg| {
+        arg.value_parser(PossibleValuesParser::new(languages))
+            .hide_possible_values(true)
+    });
+    clap_complete::generate(shell, &mut command, "cath", &mut io::stdout());
+}
+
+// Whether `line` is short enough to highlight; with --strict-highlighting every line is
+fn within_highlight_budget(line: &str, strict: bool) -> bool {
+    strict || line.len() <= MAX_HIGHLIGHT_LINE_LENGTH
+}
+
+// Whether `file_path` is over --max-highlight-size, noting on stderr that it won't be
+// highlighted. Minified or generated files can take minutes to highlight; stdin's size
+// isn't known up front, so it's always highlighted
+fn too_large_to_highlight(args: &Args, file_path: Option<&Path>) -> bool {
+    let Some(path) = file_path else {
+        return false;
+    };
+    let too_large = fs::metadata(path).is_ok_and(|m| m.len() > args.max_highlight_size);
+    if too_large {
+        eprintln!(
+            "cath: {} is larger than --max-highlight-size, printing it without highlighting",
+            path.display()
+        );
+    }
+    too_large
+}
+
+// Parse a size such as "4096", "512K", "10M" or "1G" (binary units) into bytes
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((index, _)) => value.split_at(index),
+        None => (value, ""),
+    };
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(format!("unknown size unit '{}'", unit)),
+    };
+    digits
+        .parse::<u64>()
+        .map_err(|_| format!("invalid size '{}'", value))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{}' is too large", value))
+}
+
+// How inputs are read. A mapped file that shrinks underneath us crashes the process, so
+// files being followed or watched are never memory-mapped; following picks up from a byte
+// offset in the file, so a followed file isn't transcoded, decompressed or preprocessed
+// either
+fn input_options(args: &Args) -> input::InputOptions {
+    input::InputOptions {
+        mmap: !args.no_mmap && !args.follow && !args.watch,
+        transcode: !args.follow,
+        encoding: args.encoding,
+        lossy: args.lossy,
+        tail: None,
+        decompress: !args.follow,
+        preprocessor: (!args.follow)
+            .then(|| Preprocessor::from_env(args.lessopen))
+            .flatten(),
+        hooks: if args.follow {
+            Vec::new()
+        } else {
+            args.preprocessors.clone()
+        },
+        bytes: args.bytes,
+    }
+}
+
+// Replace the arguments containing wildcards (e.g. "src\\*.rs") with the files they match,
+// in sorted order, like a Unix shell would. Arguments that name an existing file, or
+// don't match anything, are kept as they are, so the latter get a "not found" error
+fn expand_wildcards(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut expanded = Vec::with_capacity(paths.len());
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        let has_wildcards = pattern.contains(['*', '?', '[']);
+        let matches: Vec<PathBuf> = if has_wildcards && !path.exists() {
+            glob::glob(&pattern)
+                .map(|paths| paths.filter_map(Result::ok).collect())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            expanded.push(path);
+        } else {
+            expanded.extend(matches);
+        }
+    }
+    expanded
+}
+
+// The line ranges to print, with --tail as a range counted from the end for inputs that
+// couldn't be opened at their last lines directly
+fn requested_ranges(args: &Args) -> Vec<LineRange> {
+    let mut ranges = args.line_ranges.clone();
+    if let Some(lines) = args.tail {
+        ranges.push(LineRange {
+            start: Line::End(lines),
+            end: Line::Start(usize::MAX),
+        });
+    }
+    ranges
+}
+
+// Look up an encoding by any of its WHATWG labels, e.g. "utf-16le", "latin1" or "sjis"
+fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("unknown encoding '{}'", label))
+}
+
+// A line of highlighted text: its 1-based number and its styled pieces
+type HighlightedLine<'a> = (usize, Vec<(Style, &'a str)>);
+
+// Highlight `content` and return the lines whose numbers fall in `line_ranges` (all of them when
+// there are none), each with its 1-based line number. Lines before and between the ranges
+// are still highlighted so that the parse state (e.g. an open block comment) is right when
+// a range starts. `file_path` is the input the content comes from, for errors
+fn highlight_lines<'a>(
+    content: &'a str,
+    file_path: Option<&Path>,
+    syntax: &SyntaxReference,
+    ps: &SyntaxSet,
+    theme: &Theme,
+    line_ranges: &[LineRange],
+    strict: bool,
+) -> Result<Vec<HighlightedLine<'a>>, CathError> {
+    let end = line_range::last_line(line_ranges);
+    let mut h = HighlightLines::new(syntax, theme);
+    // Lines over the length budget keep the theme's plain text style
+    let default_style = Highlighter::new(theme).get_default();
+    let mut lines = Vec::new();
+    for (index, line) in LinesWithEndings::from(content).enumerate() {
+        let line_number = index + 1;
+        if line_number > end {
+            break;
+        }
+        let ranges = if within_highlight_budget(line, strict) {
+            h.highlight_line(line, ps)
+                .map_err(|err| CathError::highlight(file_path, err))?
+        } else {
+            vec![(default_style, line)]
+        };
+        if line_range::in_ranges(line_ranges, line_number) {
+            lines.push((line_number, ranges));
+        }
+    }
+    Ok(lines)
+}
+
+// The syntax named by the --file-name given for the input at `index`, if any
+fn syntax_for_file_name<'a>(
+    args: &Args,
+    index: usize,
+    ps: &'a SyntaxSet,
+) -> Option<&'a SyntaxReference> {
+    find_syntax_by_path(ps, args.file_names.get(index)?, &args.map_syntax)
+}
@@ -0,0 +1,181 @@
+// One end of a line range: a line number counted from the start of the input, or from its
+// end when given as a negative number (-1 is the last line)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Line {
+    Start(usize),
+    End(usize),
+}
+
+impl Line {
+    // The line number counted from the start, for an input of `total_lines` lines; lines
+    // counted from the end of a shorter input come out as 0, before the first line
+    fn resolve(self, total_lines: usize) -> usize {
+        match self {
+            Line::Start(number) => number,
+            Line::End(number) => (total_lines + 1).saturating_sub(number),
+        }
+    }
+}
+
+// An inclusive range of line numbers, as given to --line-range and --highlight-line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: Line,
+    pub end: Line,
+}
+
+impl LineRange {
+    // Parse a single line ("13") or a range of lines ("40:45"); lines are numbered from 1
+    // Either end of a range may be left out (":40" starts at the first line, "40:" runs to
+    // the last), "40:+5" means the five lines after line 40 as well, and negative numbers
+    // count from the end ("-50:" is the last 50 lines)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let line = |text: &str| {
+            let text = text.trim();
+            let (from_end, digits) = match text.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, text),
+            };
+            match digits.parse::<usize>() {
+                Ok(0) => Err("line numbers start at 1".to_string()),
+                Ok(number) if from_end => Ok(Line::End(number)),
+                Ok(number) => Ok(Line::Start(number)),
+                Err(_) => Err(format!("invalid line number '{}'", text)),
+            }
+        };
+        let range = match value.split_once(':') {
+            Some((start, end)) => {
+                let start = if start.trim().is_empty() {
+                    Line::Start(1)
+                } else {
+                    line(start)?
+                };
+                let end = match end.trim() {
+                    "" => Line::Start(usize::MAX),
+                    end => match end.strip_prefix('+') {
+                        Some(count) => {
+                            let count = count
+                                .parse::<usize>()
+                                .map_err(|_| format!("invalid line count '{}'", count))?;
+                            match start {
+                                Line::Start(number) => Line::Start(number.saturating_add(count)),
+                                // Going past the last line just stops there
+                                Line::End(number) => Line::End(number.saturating_sub(count).max(1)),
+                            }
+                        }
+                        None => line(end)?,
+                    },
+                };
+                LineRange { start, end }
+            }
+            None => {
+                let line = line(value)?;
+                LineRange {
+                    start: line,
+                    end: line,
+                }
+            }
+        };
+        // Ends counted from different sides can only be compared once the length is known
+        let reversed = match (range.start, range.end) {
+            (Line::Start(start), Line::Start(end)) => start > end,
+            (Line::End(start), Line::End(end)) => start < end,
+            _ => false,
+        };
+        if reversed {
+            return Err(format!("range '{}' ends before it starts", value));
+        }
+        Ok(range)
+    }
+
+    // The same range with both ends counted from the start, for an input of `total_lines`
+    // lines
+    pub fn resolve(&self, total_lines: usize) -> Self {
+        LineRange {
+            start: Line::Start(self.start.resolve(total_lines)),
+            end: Line::Start(self.end.resolve(total_lines)),
+        }
+    }
+
+    // Whether the range includes `line_number`; ends counted from the end of the input
+    // need to be resolved first, and never match before that
+    pub fn contains(&self, line_number: usize) -> bool {
+        match (self.start, self.end) {
+            (Line::Start(start), Line::Start(end)) => (start..=end).contains(&line_number),
+            _ => false,
+        }
+    }
+}
+
+// Whether `line_number` is in any of `ranges`; no ranges at all means every line is
+pub fn in_ranges(ranges: &[LineRange], line_number: usize) -> bool {
+    ranges.is_empty() || ranges.iter().any(|range| range.contains(line_number))
+}
+
+// The last line any of `ranges` asks for, so reading can stop there
+pub fn last_line(ranges: &[LineRange]) -> usize {
+    ranges
+        .iter()
+        .map(|range| match range.end {
+            Line::Start(number) => number,
+            Line::End(_) => usize::MAX,
+        })
+        .max()
+        .unwrap_or(usize::MAX)
+}
+
+// Whether any of `ranges` counts lines from the end, so the input's length must be known
+pub fn counts_from_end(ranges: &[LineRange]) -> bool {
+    ranges
+        .iter()
+        .any(|range| matches!(range.start, Line::End(_)) || matches!(range.end, Line::End(_)))
+}
+
+// Resolve all of `ranges` for an input of `total_lines` lines
+pub fn resolve(ranges: &[LineRange], total_lines: usize) -> Vec<LineRange> {
+    ranges
+        .iter()
+        .map(|range| range.resolve(total_lines))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(value: &str, total_lines: usize) -> (usize, usize) {
+        let range = LineRange::parse(value).unwrap().resolve(total_lines);
+        match range {
+            LineRange {
+                start: Line::Start(start),
+                end: Line::Start(end),
+            } => (start, end),
+            _ => unreachable!("resolved ranges count from the start"),
+        }
+    }
+
+    #[test]
+    fn parses_lines_and_ranges() {
+        assert_eq!(range("13", 100), (13, 13));
+        assert_eq!(range("40:45", 100), (40, 45));
+        assert_eq!(range(":40", 100), (1, 40));
+        assert_eq!(range("40:", 100), (40, usize::MAX));
+        assert_eq!(range("40:+5", 100), (40, 45));
+    }
+
+    #[test]
+    fn counts_negative_lines_from_the_end() {
+        assert_eq!(range("-1", 100), (100, 100));
+        assert_eq!(range("-50:", 100), (51, usize::MAX));
+        assert_eq!(range("-10:-5", 100), (91, 96));
+        // Lines before the start of a short input come out as 0
+        assert_eq!(range("-50:", 10), (0, usize::MAX));
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        for value in ["0", "x", "5:3", "-3:-5", "1:+x", ""] {
+            assert!(LineRange::parse(value).is_err(), "{}", value);
+        }
+    }
+}
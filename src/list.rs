@@ -0,0 +1,76 @@
+use std::io::{self, Write};
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::error::CathError;
+use crate::terminal::{self, ColorDepth};
+
+// Short Rust snippet rendered in every theme by --list-themes
+const THEME_SAMPLE: &str = r#"// Compute the nth Fibonacci number
+fn fibonacci(n: u32) -> u64 {
+    let (mut a, mut b) = (0, 1);
+    for _ in 0..n {
+        (a, b) = (b, a + b);
+    }
+    a
+}
+"#;
+
+// Print every available theme name followed by a highlighted preview of a sample snippet
+pub fn list_themes(
+    out: &mut dyn Write,
+    ps: &SyntaxSet,
+    ts: &ThemeSet,
+    color_depth: ColorDepth,
+) -> Result<(), CathError> {
+    let syntax = ps
+        .find_syntax_by_extension("rs")
+        .unwrap_or_else(|| ps.find_syntax_plain_text());
+
+    for (index, (name, theme)) in ts.themes.iter().enumerate() {
+        if index > 0 {
+            writeln!(out).map_err(CathError::Output)?;
+        }
+        // Theme name in bold, followed by the sample drawn on the theme's own background
+        writeln!(out, "\x1b[1mTheme: {}\x1b[0m", name).map_err(CathError::Output)?;
+        writeln!(out).map_err(CathError::Output)?;
+        let mut h = HighlightLines::new(syntax, theme);
+        for line in LinesWithEndings::from(THEME_SAMPLE) {
+            let ranges = h
+                .highlight_line(line, ps)
+                .map_err(|err| CathError::highlight(None, err))?;
+            // Strip the newline so the background color doesn't paint the rest of the row
+            let escaped = terminal::as_terminal_escaped(&ranges[..], true, color_depth);
+            writeln!(out, "{}\x1b[0m", escaped.trim_end_matches('\n'))
+                .map_err(CathError::Output)?;
+        }
+    }
+    Ok(())
+}
+
+// Print every visible syntax with its file extensions as a two-column table
+pub fn list_languages(out: &mut dyn Write, ps: &SyntaxSet) -> io::Result<()> {
+    let mut syntaxes: Vec<_> = ps.syntaxes().iter().filter(|s| !s.hidden).collect();
+    syntaxes.sort_by_key(|syntax| syntax.name.to_lowercase());
+
+    // Pad the name column to the longest name so the extensions line up
+    let width = syntaxes
+        .iter()
+        .map(|syntax| syntax.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    writeln!(out, "\x1b[1m{:width$}  Extensions\x1b[0m", "Language")?;
+    for syntax in syntaxes {
+        writeln!(
+            out,
+            "{:width$}  {}",
+            syntax.name,
+            syntax.file_extensions.join(", ")
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+// A place in a file, as compilers and grep point at one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    // Counted in characters from 1
+    pub column: Option<usize>,
+}
+
+// Split a "file:line" or "file:line:column" argument (a trailing colon, as error messages
+// have, is allowed) into the file and the location in it. None for anything else,
+// including files that exist with such a name
+pub fn split(path: &Path) -> Option<(PathBuf, Location)> {
+    if path.exists() {
+        return None;
+    }
+    let text = path.to_str()?;
+    let text = text.strip_suffix(':').unwrap_or(text);
+    let number = |piece: &str| {
+        piece
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0 && piece.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let (rest, last) = text.rsplit_once(':')?;
+    let last = number(last)?;
+    let (file, location) = match rest.rsplit_once(':') {
+        Some((file, line)) if let Some(line) = number(line) => (
+            file,
+            Location {
+                line,
+                column: Some(last),
+            },
+        ),
+        _ => (
+            rest,
+            Location {
+                line: last,
+                column: None,
+            },
+        ),
+    };
+    let file = Path::new(file);
+    file.is_file().then(|| (file.to_path_buf(), location))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tests run in the crate's directory, where Cargo.toml is a file that exists
+    fn split_str(text: &str) -> Option<(PathBuf, Location)> {
+        split(Path::new(text))
+    }
+
+    #[test]
+    fn splits_line_and_column() {
+        let file = PathBuf::from("Cargo.toml");
+        assert_eq!(
+            split_str("Cargo.toml:12"),
+            Some((
+                file.clone(),
+                Location {
+                    line: 12,
+                    column: None
+                }
+            ))
+        );
+        assert_eq!(
+            split_str("Cargo.toml:12:3:"),
+            Some((
+                file,
+                Location {
+                    line: 12,
+                    column: Some(3)
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn leaves_other_paths_alone() {
+        for text in [
+            "Cargo.toml",
+            "Cargo.toml:0",
+            "Cargo.toml:x",
+            "Cargo.toml:+1",
+            "missing.rs:3",
+        ] {
+            assert_eq!(split_str(text), None, "{}", text);
+        }
+    }
+}
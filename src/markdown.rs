@@ -0,0 +1,497 @@
+use std::fmt::Write as _;
+
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::detect::find_syntax_by_language;
+use crate::hyperlink;
+use crate::terminal::{self, ColorDepth};
+
+// Styles for the parts of a document; code blocks get the theme's colors instead
+const HEADING_1: &str = "\x1b[1;4;35m";
+const HEADING: &str = "\x1b[1;35m";
+const EMPHASIS: &str = "\x1b[3m";
+const STRONG: &str = "\x1b[1m";
+const STRIKETHROUGH: &str = "\x1b[9m";
+const LINK: &str = "\x1b[4;34m";
+const CODE: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// How a document is laid out
+pub struct MarkdownOptions {
+    // Terminal width that paragraphs are wrapped at
+    pub width: usize,
+    pub color_depth: ColorDepth,
+    // Make links clickable with OSC 8, instead of showing their targets after them
+    pub hyperlinks: bool,
+}
+
+// Render a Markdown document for the terminal: headings, emphasis, lists, block quotes and
+// tables get terminal styling, paragraphs are wrapped to the width, and fenced code blocks
+// are highlighted with the syntax named after the fence
+pub fn render(text: &str, ps: &SyntaxSet, theme: &Theme, options: &MarkdownOptions) -> String {
+    let parser = Parser::new_ext(
+        text,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+    );
+    let mut renderer = Renderer {
+        ps,
+        theme,
+        options,
+        out: String::new(),
+        line: String::new(),
+        column: 0,
+        line_started: false,
+        line_has_text: false,
+        pending_space: false,
+        gap: false,
+        prefixes: Vec::new(),
+        marker: None,
+        styles: Vec::new(),
+        applied: String::new(),
+        lists: Vec::new(),
+        links: Vec::new(),
+        code: None,
+        table: None,
+    };
+    for event in parser {
+        renderer.event(event);
+    }
+    renderer.end_line();
+    renderer.out
+}
+
+// A table being collected, which can only be laid out once all its cells are known
+struct Table {
+    alignments: Vec<Alignment>,
+    // Rows of cells, each cell already styled; the first row is the header
+    rows: Vec<Vec<String>>,
+}
+
+struct Renderer<'a> {
+    ps: &'a SyntaxSet,
+    theme: &'a Theme,
+    options: &'a MarkdownOptions,
+    out: String,
+    // The line being filled, and how many columns of it are taken
+    line: String,
+    column: usize,
+    // Whether the prefixes have been written for the current line, and whether any text
+    // has come after them
+    line_started: bool,
+    line_has_text: bool,
+    // There was white space before the next word
+    pending_space: bool,
+    // A block just ended, so the next one is set apart from it with a blank line
+    gap: bool,
+    // What every line of the current block starts with: quote bars and list indentation
+    prefixes: Vec<String>,
+    // The bullet or number of a list item that just started, which takes the place of its
+    // indentation on its first line
+    marker: Option<String>,
+    // Inline styles in effect (including the OSC 8 sequence of an open link), and the
+    // ones last switched to on the current line. Switching only happens right before a
+    // word, so spaces between differently styled words aren't underlined or linked
+    styles: Vec<String>,
+    applied: String,
+    // The next number of each open list, or None for bulleted lists
+    lists: Vec<Option<u64>>,
+    // Targets of the open links
+    links: Vec<String>,
+    // The language and text of the code block being collected
+    code: Option<(String, String)>,
+    table: Option<Table>,
+}
+
+impl Renderer<'_> {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(&text);
+                } else {
+                    self.text(&text);
+                }
+            }
+            Event::Code(code) => {
+                self.push_style(CODE);
+                self.text(&code);
+                self.pop_style();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                self.push_style(DIM);
+                self.text(&html);
+                self.pop_style();
+            }
+            Event::SoftBreak => self.pending_space = true,
+            Event::HardBreak => self.end_line(),
+            Event::Rule => {
+                self.start_block();
+                let width = self.options.width.saturating_sub(self.prefix_width());
+                self.start_line();
+                let _ = write!(self.line, "{}{}{}", DIM, "─".repeat(width), RESET);
+                self.end_block();
+            }
+            Event::TaskListMarker(checked) => self.text(if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(name) => self.text(&format!("[^{}]", name)),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(),
+            Tag::Heading { level, .. } => {
+                self.start_block();
+                self.push_style(if level == HeadingLevel::H1 {
+                    HEADING_1
+                } else {
+                    HEADING
+                });
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                self.prefixes.push(format!("{}│{} ", DIM, RESET));
+            }
+            Tag::CodeBlock(kind) => {
+                self.start_block();
+                let language = match kind {
+                    // The info string can go on after the language, e.g. "rust,ignore"
+                    CodeBlockKind::Fenced(info) => info
+                        .split([',', ' ', '{'])
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                // A list inside an item starts on a line of its own, without a blank line
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else {
+                    self.end_line();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.end_line();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.prefixes
+                    .push(" ".repeat(terminal::display_width(&marker)));
+                self.marker = Some(marker);
+                // Loose items hold paragraphs, which shouldn't start with a gap
+                self.gap = false;
+            }
+            Tag::Table(alignments) => {
+                self.start_block();
+                self.table = Some(Table {
+                    alignments,
+                    rows: Vec::new(),
+                });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push(String::new());
+                }
+            }
+            Tag::Emphasis => self.push_style(EMPHASIS),
+            Tag::Strong => self.push_style(STRONG),
+            Tag::Strikethrough => self.push_style(STRIKETHROUGH),
+            Tag::Link { dest_url, .. } => {
+                if self.options.hyperlinks {
+                    self.push_style(hyperlink::osc8(&dest_url));
+                }
+                self.links.push(dest_url.to_string());
+                self.push_style(LINK);
+            }
+            Tag::Image { .. } => {
+                self.push_style(DIM);
+                self.text("[image: ");
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.end_block(),
+            TagEnd::Heading(_) => {
+                self.pop_style();
+                self.end_block();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.end_line();
+                self.prefixes.pop();
+                self.gap = true;
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    self.write_code(&language, &code);
+                }
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            TagEnd::Item => {
+                self.end_line();
+                self.prefixes.pop();
+                self.marker = None;
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.write_table(&table);
+                }
+                self.end_block();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => self.pop_style(),
+            TagEnd::Link => {
+                self.pop_style();
+                let target = self.links.pop().unwrap_or_default();
+                if self.options.hyperlinks {
+                    self.pop_style();
+                } else if !target.is_empty() && !self.line.ends_with(&target) {
+                    self.push_style(DIM);
+                    self.text(&format!(" <{}>", target));
+                    self.pop_style();
+                }
+            }
+            TagEnd::Image => {
+                self.text("]");
+                self.pop_style();
+            }
+            _ => {}
+        }
+    }
+
+    // Write text, breaking lines between words where it would go past the width. In a
+    // table, text goes into the current cell as it is
+    fn text(&mut self, text: &str) {
+        if let Some(cell) = self.current_cell() {
+            cell.push_str(text);
+            return;
+        }
+        let starts_with_space = text.starts_with(char::is_whitespace);
+        let ends_with_space = text.ends_with(char::is_whitespace);
+        let mut words = text.split_whitespace().peekable();
+        if starts_with_space {
+            self.pending_space = true;
+        }
+        while let Some(word) = words.next() {
+            let width = terminal::display_width(word);
+            let space = usize::from(self.pending_space && self.line_has_text);
+            if self.line_has_text && self.column + space + width > self.options.width {
+                self.end_line();
+            }
+            self.start_line();
+            let wanted = self.styles.concat();
+            if wanted != self.applied {
+                self.close_styles();
+            }
+            if self.pending_space && self.line_has_text {
+                self.line.push(' ');
+                self.column += 1;
+            }
+            if wanted != self.applied {
+                self.line.push_str(&wanted);
+                self.applied = wanted;
+            }
+            self.line.push_str(word);
+            self.column += width;
+            self.line_has_text = true;
+            self.pending_space = words.peek().is_some() || ends_with_space;
+        }
+    }
+
+    fn current_cell(&mut self) -> Option<&mut String> {
+        self.table
+            .as_mut()
+            .and_then(|table| table.rows.last_mut())
+            .and_then(|row| row.last_mut())
+    }
+
+    fn push_style(&mut self, style: impl Into<String>) {
+        self.styles.push(style.into());
+        self.restyle_cell(false);
+    }
+
+    fn pop_style(&mut self) {
+        let closes_link = self
+            .styles
+            .pop()
+            .is_some_and(|style| style.starts_with("\x1b]8"));
+        self.restyle_cell(closes_link);
+    }
+
+    // Text in table cells isn't wrapped, so style changes are written into the cell right
+    // away, by resetting everything and starting the styles still in effect
+    fn restyle_cell(&mut self, closes_link: bool) {
+        let styles = self.styles.concat();
+        if let Some(cell) = self.current_cell() {
+            if closes_link {
+                cell.push_str(&hyperlink::osc8(""));
+            }
+            cell.push_str(RESET);
+            cell.push_str(&styles);
+        }
+    }
+
+    // End the styles switched to on the current line, and the link if one is open
+    fn close_styles(&mut self) {
+        if self.applied.contains("\x1b]8;;") {
+            self.line.push_str(&hyperlink::osc8(""));
+        }
+        if !self.applied.is_empty() {
+            self.line.push_str(RESET);
+        }
+        self.applied.clear();
+    }
+
+    fn prefix_width(&self) -> usize {
+        self.prefixes
+            .iter()
+            .map(|prefix| terminal::display_width(prefix))
+            .sum()
+    }
+
+    // Write the prefixes for a new line, if that hasn't been done yet
+    fn start_line(&mut self) {
+        if self.line_started {
+            return;
+        }
+        self.line_started = true;
+        let count = self.prefixes.len();
+        for (index, prefix) in self.prefixes.iter().enumerate() {
+            // An item's marker stands in for its indentation on the item's first line
+            match &self.marker {
+                Some(marker) if index + 1 == count => self.line.push_str(marker),
+                _ => self.line.push_str(prefix),
+            }
+        }
+        self.marker = None;
+        self.column = self.prefix_width();
+    }
+
+    // Finish the current line, if one was started
+    fn end_line(&mut self) {
+        if !self.line_started {
+            return;
+        }
+        self.close_styles();
+        self.out.push_str(&self.line);
+        self.out.push('\n');
+        self.line.clear();
+        self.line_started = false;
+        self.line_has_text = false;
+        self.pending_space = false;
+    }
+
+    // Set a block apart from the one before it, with a blank line that keeps the quote bars
+    fn start_block(&mut self) {
+        self.end_line();
+        if self.gap && !self.out.is_empty() {
+            let bars: String = self
+                .prefixes
+                .iter()
+                .filter(|prefix| prefix.contains('│'))
+                .cloned()
+                .collect();
+            self.out.push_str(bars.trim_end());
+            self.out.push('\n');
+        }
+        self.gap = false;
+    }
+
+    fn end_block(&mut self) {
+        self.end_line();
+        self.gap = true;
+    }
+
+    // Write a code block, highlighted with the syntax for `language` and indented past the
+    // prefixes; code is never wrapped
+    fn write_code(&mut self, language: &str, code: &str) {
+        let syntax = Some(language)
+            .filter(|language| !language.is_empty())
+            .and_then(|language| find_syntax_by_language(self.ps, language))
+            .unwrap_or_else(|| self.ps.find_syntax_plain_text());
+        let mut h = HighlightLines::new(syntax, self.theme);
+        for line in LinesWithEndings::from(code) {
+            let ranges = h.highlight_line(line, self.ps).unwrap_or_default();
+            let text = terminal::as_terminal_escaped(&ranges, false, self.options.color_depth);
+            self.start_line();
+            self.line.push_str("  ");
+            self.line.push_str(text.trim_end_matches(['\n', '\r']));
+            self.line.push_str(RESET);
+            self.line_has_text = true;
+            self.end_line();
+        }
+    }
+
+    // Lay out a table with its columns aligned as the delimiter row asks, the header in
+    // bold and a line under it
+    fn write_table(&mut self, table: &Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| terminal::display_width(cell))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for (index, row) in table.rows.iter().enumerate() {
+            self.start_line();
+            for (column, &width) in widths.iter().enumerate() {
+                if column > 0 {
+                    let _ = write!(self.line, " {}│{} ", DIM, RESET);
+                }
+                let cell = row.get(column).map_or("", String::as_str);
+                let padding = width - terminal::display_width(cell);
+                let (before, after) = match table.alignments.get(column) {
+                    Some(Alignment::Right) => (padding, 0),
+                    Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+                    _ => (0, padding),
+                };
+                let style = if index == 0 { STRONG } else { "" };
+                let _ = write!(
+                    self.line,
+                    "{:before$}{}{}{}{:after$}",
+                    "", style, cell, RESET, ""
+                );
+            }
+            self.line_has_text = true;
+            self.end_line();
+            if index == 0 {
+                self.start_line();
+                let rule: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+                let _ = write!(self.line, "{}{}{}", DIM, rule.join("─┼─"), RESET);
+                self.end_line();
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+use crate::detect::find_syntax_by_language;
+
+// Whether an argument is an http(s) URL to fetch rather than a file path
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|text| text.starts_with("http://") || text.starts_with("https://"))
+}
+
+// The path to detect the syntax of an input by: for a URL, the path part without the query
+// and fragment (e.g. /src/main.rs), which goes by its extension like a file name does
+pub fn content_path(path: &Path) -> PathBuf {
+    let Some(text) = path.to_str().filter(|_| is_url(path)) else {
+        return path.to_path_buf();
+    };
+    let after_scheme = &text[text.find("://").map_or(0, |index| index + 3)..];
+    let url_path = after_scheme
+        .find('/')
+        .map_or("", |index| &after_scheme[index..]);
+    let end = url_path.find(['?', '#']).unwrap_or(url_path.len());
+    PathBuf::from(&url_path[..end])
+}
+
+// Start fetching `url`, returning a reader for the body as it streams in and the
+// Content-Type the server sent, if any. Error statuses (404 and the like) are errors
+#[cfg(feature = "net")]
+pub fn fetch(url: &str) -> io::Result<(Box<dyn BufRead>, Option<String>)> {
+    // ureq's own messages repeat the URL, which the caller already shows
+    let response = ureq::get(url).call().map_err(|err| match err {
+        ureq::Error::Status(code, response) => {
+            io::Error::other(format!("HTTP {} {}", code, response.status_text()))
+        }
+        ureq::Error::Transport(transport) => io::Error::other(
+            transport
+                .message()
+                .map_or_else(|| transport.kind().to_string(), str::to_string),
+        ),
+    })?;
+    let content_type = response.header("Content-Type").map(str::to_string);
+    let reader = io::BufReader::new(response.into_reader());
+    Ok((Box::new(reader), content_type))
+}
+
+#[cfg(not(feature = "net"))]
+pub fn fetch(_url: &str) -> io::Result<(Box<dyn BufRead>, Option<String>)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reading URLs needs cath to be built with the net feature",
+    ))
+}
+
+// Media types whose syntax isn't just their subtype, as (type, language) pairs
+const CONTENT_TYPES: &[(&str, &str)] = &[
+    ("application/javascript", "js"),
+    ("application/x-javascript", "js"),
+    ("application/x-sh", "sh"),
+    ("application/x-httpd-php", "php"),
+    ("application/xhtml+xml", "html"),
+    ("text/x-shellscript", "sh"),
+    ("text/x-python", "py"),
+    ("text/x-c", "c"),
+    ("text/x-c++", "cpp"),
+    ("text/x-java", "java"),
+    ("text/x-rust", "rs"),
+];
+
+// Look up a syntax from a Content-Type header like "application/json; charset=utf-8". Types
+// that say nothing about the syntax, like text/plain or application/octet-stream, give None
+pub fn find_syntax_by_content_type<'a>(
+    ps: &'a SyntaxSet,
+    content_type: &str,
+) -> Option<&'a SyntaxReference> {
+    let media_type = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    if let Some(&(_, language)) = CONTENT_TYPES.iter().find(|(name, _)| *name == media_type) {
+        return find_syntax_by_language(ps, language);
+    }
+    let (_, subtype) = media_type.split_once('/')?;
+    // Structured syntax suffixes, e.g. application/ld+json or image/svg+xml
+    let subtype = subtype.rsplit('+').next()?;
+    let language = subtype.strip_prefix("x-").unwrap_or(subtype);
+    if matches!(language, "plain" | "octet-stream") {
+        return None;
+    }
+    find_syntax_by_language(ps, language)
+}
@@ -0,0 +1,123 @@
+use serde_json::Value;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::detect::find_syntax_by_language;
+use crate::markdown::{self, MarkdownOptions};
+use crate::terminal;
+
+const PROMPT: &str = "\x1b[2;32m";
+const OUTPUT_PROMPT: &str = "\x1b[2;31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// Render a Jupyter notebook: its cells in order, with Markdown cells rendered, code cells
+// highlighted in the notebook's language under an "In [n]:" prompt, and their text
+// outputs after them. None if `text` isn't a notebook, so it can be shown as JSON instead
+pub fn render(
+    text: &str,
+    ps: &SyntaxSet,
+    theme: &Theme,
+    options: &MarkdownOptions,
+) -> Option<String> {
+    let notebook: Value = serde_json::from_str(text).ok()?;
+    let cells = notebook.get("cells")?.as_array()?;
+    let metadata = &notebook["metadata"];
+    let language = metadata["kernelspec"]["language"]
+        .as_str()
+        .or_else(|| metadata["language_info"]["name"].as_str())
+        .unwrap_or("python");
+    let syntax =
+        find_syntax_by_language(ps, language).unwrap_or_else(|| ps.find_syntax_plain_text());
+
+    let mut out = String::new();
+    for (index, cell) in cells.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        let source = joined(&cell["source"]);
+        match cell["cell_type"].as_str() {
+            Some("markdown") => out.push_str(&markdown::render(&source, ps, theme, options)),
+            Some("code") => {
+                let count = cell["execution_count"]
+                    .as_u64()
+                    .map_or(" ".to_string(), |count| count.to_string());
+                out.push_str(&format!("{}In [{}]:{}\n", PROMPT, count, RESET));
+                let mut h = HighlightLines::new(syntax, theme);
+                for line in LinesWithEndings::from(&source) {
+                    let ranges = h.highlight_line(line, ps).unwrap_or_default();
+                    let line = terminal::as_terminal_escaped(&ranges, false, options.color_depth);
+                    out.push_str(&format!(
+                        "  {}{}\n",
+                        line.trim_end_matches(['\n', '\r']),
+                        RESET
+                    ));
+                }
+                if let Some(outputs) = cell["outputs"]
+                    .as_array()
+                    .filter(|outputs| !outputs.is_empty())
+                {
+                    out.push_str(&format!("{}Out[{}]:{}\n", OUTPUT_PROMPT, count, RESET));
+                    for output in outputs {
+                        write_output(&mut out, output);
+                    }
+                }
+            }
+            // Raw cells are passed through untouched by nbconvert, so they're shown as is
+            _ => {
+                for line in source.lines() {
+                    out.push_str(&format!("{}{}\n", line, RESET));
+                }
+            }
+        }
+    }
+    Some(out)
+}
+
+// Write one output of a code cell: printed text, the plain text form of a result (other
+// forms like images are only named), or an error's traceback
+fn write_output(out: &mut String, output: &Value) {
+    let text = match output["output_type"].as_str() {
+        Some("stream") => joined(&output["text"]),
+        Some("execute_result" | "display_data") => {
+            let data = &output["data"];
+            match data.get("text/plain") {
+                Some(text) => joined(text),
+                None => data
+                    .as_object()
+                    .map(|data| {
+                        data.keys()
+                            .map(|kind| format!("{}[{} output]{}\n", DIM, kind, RESET))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            }
+        }
+        // Tracebacks come with their own color escapes
+        Some("error") => output["traceback"]
+            .as_array()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|line| format!("{}\n", line))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    for line in text.lines() {
+        out.push_str(&format!("  {}{}\n", line, RESET));
+    }
+}
+
+// Notebooks store multi-line text either as one string or as a list of lines
+fn joined(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,213 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use clap::ValueEnum;
+
+use crate::error::CathError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pager::Pager;
+
+// When to send output through a pager
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PagingMode {
+    // Page only when stdout is a terminal (less -F then skips paging short output)
+    Auto,
+    // Always page, even when the output fits on one screen
+    Always,
+    // Never page
+    Never,
+}
+
+// Where rendered output goes: straight to stdout, into the stdin of a pager process, into
+// a buffer that the built-in pager shows once everything has been rendered, or into a file
+pub enum OutputType {
+    Pager(Child),
+    BuiltinPager {
+        buffer: Vec<u8>,
+        // Only the pager itself reads this, and wasm builds don't have one
+        #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+        always: bool,
+    },
+    Stdout(io::Stdout),
+    File(AtomicFile),
+}
+
+// A file written through a temporary file next to it, which only replaces the destination
+// once all output has been written, so readers never see a half-written file
+pub struct AtomicFile {
+    file: File,
+    temp: PathBuf,
+    dest: PathBuf,
+}
+
+impl OutputType {
+    // Pick the output for `mode`. If the external pager can't be started (or the pager is
+    // set to "builtin") the built-in pager is used, which needs stdout to be a terminal.
+    // `chop` tells less to cut long lines off rather than wrap them
+    pub fn from_mode(mode: PagingMode, chop: bool) -> Self {
+        let is_terminal = io::stdout().is_terminal();
+        let use_pager = match mode {
+            PagingMode::Always => true,
+            PagingMode::Auto => is_terminal,
+            PagingMode::Never => false,
+        };
+        if use_pager {
+            let pager = pager_command();
+            if pager.trim() != "builtin"
+                && let Some(child) = spawn_pager(&pager, mode, chop)
+            {
+                return OutputType::Pager(child);
+            }
+            if is_terminal {
+                return OutputType::BuiltinPager {
+                    buffer: Vec::new(),
+                    always: mode == PagingMode::Always,
+                };
+            }
+        }
+        OutputType::Stdout(io::stdout())
+    }
+
+    // Write to the file at `path` instead of stdout; an existing file is only replaced when
+    // `force` is set
+    pub fn to_file(path: &Path, force: bool) -> io::Result<Self> {
+        if !force && path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists (use --force to overwrite)",
+                    path.display()
+                ),
+            ));
+        }
+        // The temporary file must be on the same file system for the rename to be atomic
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = path.with_file_name(format!(".{}.cath-{}.tmp", name, std::process::id()));
+        let file = File::create(&temp)?;
+        Ok(OutputType::File(AtomicFile {
+            file,
+            temp,
+            dest: path.to_path_buf(),
+        }))
+    }
+
+    // The writer that output should be sent to
+    pub fn handle(&mut self) -> &mut dyn Write {
+        match self {
+            OutputType::Pager(child) => child.stdin.as_mut().expect("pager stdin is piped"),
+            OutputType::BuiltinPager { buffer, .. } => buffer,
+            OutputType::Stdout(stdout) => stdout,
+            OutputType::File(atomic) => &mut atomic.file,
+        }
+    }
+}
+
+impl Drop for OutputType {
+    // Close the pager's stdin so it sees the end of the output, then wait for the user to
+    // quit it; for the built-in pager, this is where the buffered output is finally shown,
+    // and for a file, where it's moved into place
+    fn drop(&mut self) {
+        match self {
+            OutputType::Pager(child) => {
+                child.stdin = None;
+                let _ = child.wait();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            OutputType::BuiltinPager { buffer, always } => {
+                if let Err(err) = Pager::new(buffer).run(*always) {
+                    eprintln!("cath: pager error: {}", err);
+                }
+            }
+            // wasm builds have no terminal to page on, so the output is written as it is
+            #[cfg(target_arch = "wasm32")]
+            OutputType::BuiltinPager { buffer, .. } => {
+                let _ = io::stdout().write_all(buffer);
+            }
+            OutputType::Stdout(_) => {}
+            OutputType::File(atomic) => {
+                // Output cut short by a panic is thrown away rather than replacing the file
+                if std::thread::panicking() {
+                    let _ = fs::remove_file(&atomic.temp);
+                    return;
+                }
+                let result = atomic
+                    .file
+                    .sync_all()
+                    .and_then(|_| fs::rename(&atomic.temp, &atomic.dest));
+                if let Err(err) = result {
+                    let _ = fs::remove_file(&atomic.temp);
+                    eprintln!("cath: failed to write {}: {}", atomic.dest.display(), err);
+                }
+            }
+        }
+    }
+}
+
+// Let writing into a closed pipe end the program, as it does other Unix tools, instead of
+// each write failing with EPIPE; Rust ignores SIGPIPE by default. Only the command line
+// does this, since a program embedding cath may rely on the signal being ignored
+pub fn reset_sigpipe() {
+    #[cfg(unix)]
+    // SAFETY: this runs before any other threads are started, and SIG_DFL is a valid
+    // disposition for SIGPIPE
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+// Give up on the output after writing it failed. A reader that went away before the end
+// (head, or a pager quit early) has all it wanted, so that exits quietly and successfully
+pub fn write_failed(err: io::Error) -> ! {
+    exit_failed(CathError::Output(err))
+}
+
+// Report `err` and exit with status 1, for the errors nothing can be printed after; a
+// closed output exits quietly as above
+pub fn exit_failed(err: CathError) -> ! {
+    if err.is_broken_pipe() {
+        std::process::exit(0);
+    }
+    eprintln!("cath: {}", err);
+    std::process::exit(1);
+}
+
+// The pager named by CATH_PAGER or PAGER, defaulting to less
+fn pager_command() -> String {
+    env::var("CATH_PAGER")
+        .ok()
+        .or_else(|| env::var("PAGER").ok())
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string())
+}
+
+// Start `pager` with a piped stdin, or None if it isn't installed
+fn spawn_pager(pager: &str, mode: PagingMode, chop: bool) -> Option<Child> {
+    let mut words = shlex::split(pager)?;
+    if words.is_empty() {
+        return None;
+    }
+    let program = words.remove(0);
+
+    let mut command = Command::new(&program);
+    let is_less = Path::new(&program)
+        .file_stem()
+        .is_some_and(|stem| stem == "less");
+    if is_less && words.is_empty() {
+        // -R passes our color escapes through; -F quits immediately when the output fits on
+        // one screen, which is what makes paging "automatic"; -S chops long lines
+        let mut flags = String::from("-R");
+        if mode != PagingMode::Always {
+            flags.push('F');
+        }
+        if chop {
+            flags.push('S');
+        }
+        command.arg(flags);
+    } else {
+        command.args(&words);
+    }
+    command.stdin(Stdio::piped()).spawn().ok()
+}
@@ -0,0 +1,89 @@
+use crate::terminal;
+
+const BOLD: &str = "\x1b[1m";
+const UNDERLINE: &str = "\x1b[4m";
+// Turns off bold and underline, leaving colors alone
+const NORMAL: &str = "\x1b[22;24m";
+
+// How a character was overstruck
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Emphasis {
+    pub bold: bool,
+    pub underline: bool,
+}
+
+// Split a line written for a printer, as man does for pagers, into its plain text and the
+// emphasis of each character in it. Striking a character over itself ("X\bX") makes it
+// bold and striking it over an underscore ("_\bX") underlines it; a character struck over
+// anything else replaces it
+pub fn parse(line: &str) -> (String, Vec<Emphasis>) {
+    let mut text = String::with_capacity(line.len());
+    let mut emphasis = Vec::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(mut c) = chars.next() {
+        if c == '\x08' {
+            continue;
+        }
+        let mut style = Emphasis::default();
+        while chars.next_if_eq(&'\x08').is_some() {
+            let Some(next) = chars.next() else {
+                break;
+            };
+            if next == c {
+                style.bold = true;
+            } else if c == '_' {
+                style.underline = true;
+                c = next;
+            } else if next == '_' {
+                style.underline = true;
+            } else {
+                c = next;
+            }
+        }
+        text.push(c);
+        emphasis.push(style);
+    }
+    (text, emphasis)
+}
+
+// Put the emphasis back into `text`, the plain text of an overstruck line possibly with
+// color escapes added by highlighting, as bold and underline escapes. `emphasis` has one
+// entry per character of the text outside of escapes
+pub fn restyle(text: &str, emphasis: &[Emphasis]) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut current = Emphasis::default();
+    let mut index = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let len = terminal::escape_len(rest);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        // The line ending is left plain
+        let wanted = match c {
+            '\n' | '\r' => Emphasis::default(),
+            _ => emphasis.get(index).copied().unwrap_or_default(),
+        };
+        if wanted != current {
+            if current != Emphasis::default() {
+                out.push_str(NORMAL);
+            }
+            if wanted.bold {
+                out.push_str(BOLD);
+            }
+            if wanted.underline {
+                out.push_str(UNDERLINE);
+            }
+            current = wanted;
+        }
+        out.push(c);
+        index += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+    if current != Emphasis::default() {
+        out.push_str(NORMAL);
+    }
+    out
+}
@@ -0,0 +1,217 @@
+use std::io::{self, Write};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{
+    self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+    enable_raw_mode,
+};
+use crossterm::{execute, queue};
+
+use crate::terminal::{escape_len, strip_ansi};
+
+// A minimal interactive pager used when no external pager is available
+// The whole output is buffered up front and then shown one screen at a time
+pub struct Pager {
+    // Output lines, still containing their ANSI color escapes
+    lines: Vec<String>,
+    // Index of the first line on screen
+    top: usize,
+    // Text typed after '/' while a search is being entered
+    prompt: Option<String>,
+    // The last search pattern, reused by 'n' and 'N'
+    pattern: Option<String>,
+    // One-off message shown in the status line (e.g. "Pattern not found")
+    message: Option<String>,
+}
+
+impl Pager {
+    pub fn new(output: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(output);
+        Pager {
+            lines: text.lines().map(str::to_string).collect(),
+            top: 0,
+            prompt: None,
+            pattern: None,
+            message: None,
+        }
+    }
+
+    // Show the buffered output; it's printed directly when it fits on one screen
+    // unless `always` is set, mirroring `less -F`
+    pub fn run(mut self, always: bool) -> io::Result<()> {
+        let (_, rows) = terminal::size()?;
+        let mut stdout = io::stdout();
+        if !always && self.lines.len() < rows as usize {
+            for line in &self.lines {
+                writeln!(stdout, "{}", line)?;
+            }
+            return stdout.flush();
+        }
+
+        enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+        let result = self.event_loop(&mut stdout);
+        // Always restore the terminal, even if drawing or reading events failed
+        execute!(stdout, Show, LeaveAlternateScreen)?;
+        disable_raw_mode()?;
+        result
+    }
+
+    // Draw the screen and react to key presses until the user quits
+    fn event_loop(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        loop {
+            self.draw(stdout)?;
+            match event::read()? {
+                Event::Key(key)
+                    if key.kind != KeyEventKind::Release && !self.handle_key(key)? =>
+                {
+                    return Ok(());
+                }
+                // Resizing just needs a redraw, which happens at the top of the loop
+                _ => {}
+            }
+        }
+    }
+
+    // Number of rows available for content (the last row is the status line)
+    fn page_height() -> io::Result<usize> {
+        let (_, rows) = terminal::size()?;
+        Ok((rows as usize).saturating_sub(1).max(1))
+    }
+
+    // Largest valid value of `top`, so the last page is always full
+    fn max_top(&self) -> io::Result<usize> {
+        Ok(self.lines.len().saturating_sub(Self::page_height()?))
+    }
+
+    // Apply a key press, returning false when the pager should quit
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<bool> {
+        // While typing a search pattern, keys edit the prompt instead of scrolling
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Enter => {
+                    let pattern = prompt.clone();
+                    self.prompt = None;
+                    if !pattern.is_empty() {
+                        self.pattern = Some(pattern);
+                    }
+                    self.search(true)?;
+                }
+                KeyCode::Esc => self.prompt = None,
+                // Backspacing past the start of the prompt cancels the search
+                KeyCode::Backspace if prompt.is_empty() => self.prompt = None,
+                KeyCode::Backspace => {
+                    prompt.pop();
+                }
+                KeyCode::Char(c) => prompt.push(c),
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        self.message = None;
+        let page = Self::page_height()?;
+        let max_top = self.max_top()?;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false);
+            }
+            KeyCode::Down | KeyCode::Enter | KeyCode::Char('j') => {
+                self.top = (self.top + 1).min(max_top);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.top = self.top.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f') => {
+                self.top = (self.top + page).min(max_top);
+            }
+            KeyCode::PageUp | KeyCode::Char('b') => self.top = self.top.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => self.top = 0,
+            KeyCode::End | KeyCode::Char('G') => self.top = max_top,
+            KeyCode::Char('/') => self.prompt = Some(String::new()),
+            KeyCode::Char('n') => self.search(true)?,
+            KeyCode::Char('N') => self.search(false)?,
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    // Scroll to the next (or previous) line containing the current pattern
+    fn search(&mut self, forward: bool) -> io::Result<()> {
+        let Some(pattern) = &self.pattern else {
+            return Ok(());
+        };
+        let matches = |index: &usize| strip_ansi(&self.lines[*index]).contains(pattern.as_str());
+        let found = if forward {
+            (self.top + 1..self.lines.len()).find(matches)
+        } else {
+            (0..self.top).rev().find(matches)
+        };
+        match found {
+            Some(index) => self.top = index.min(self.max_top()?),
+            None => self.message = Some("Pattern not found".to_string()),
+        }
+        Ok(())
+    }
+
+    // Redraw the visible lines plus the status line
+    fn draw(&self, stdout: &mut io::Stdout) -> io::Result<()> {
+        let (columns, _) = terminal::size()?;
+        let page = Self::page_height()?;
+        queue!(stdout, MoveTo(0, 0), Clear(ClearType::All))?;
+        for (row, line) in self.lines.iter().skip(self.top).take(page).enumerate() {
+            queue!(
+                stdout,
+                MoveTo(0, row as u16),
+                Print(truncate_ansi(line, columns as usize)),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        // The status line shows the search prompt, a message, or the current position
+        let status = match (&self.prompt, &self.message) {
+            (Some(prompt), _) => format!("/{}", prompt),
+            (None, Some(message)) => message.clone(),
+            (None, None) => {
+                let last = (self.top + page).min(self.lines.len());
+                format!(
+                    "lines {}-{}/{} (q to quit, / to search)",
+                    self.top + 1,
+                    last,
+                    self.lines.len()
+                )
+            }
+        };
+        queue!(
+            stdout,
+            MoveTo(0, page as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(truncate_ansi(&status, columns as usize)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        stdout.flush()
+    }
+}
+
+// Cut a line down to `width` visible characters, keeping every escape sequence intact so
+// colors still apply to the part that is shown
+fn truncate_ansi(line: &str, width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut visible = 0;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let len = escape_len(rest);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        if visible < width {
+            out.push(c);
+        }
+        visible += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
@@ -0,0 +1,131 @@
+use regex::Regex;
+
+use crate::terminal;
+
+// The character ranges (counted in characters, not bytes) of `line` that any of
+// `patterns` match, in order and with overlapping ones merged
+pub fn match_ranges(patterns: &[Regex], line: &str) -> Vec<(usize, usize)> {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let char_index = |byte: usize| content[..byte].chars().count();
+    let mut ranges: Vec<(usize, usize)> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.find_iter(content))
+        .filter(|found| !found.is_empty())
+        .map(|found| (char_index(found.start()), char_index(found.end())))
+        .collect();
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// Overlay the `style` escape on the `ranges` of the characters of `text`, a line with
+// color escapes in it, on top of its colors: the style is given again after every escape
+// inside a match, and after one the line goes back to `after` (what the line's background
+// was) and the last escape before it, so the colors carry on as they were
+pub fn overlay(text: &str, ranges: &[(usize, usize)], style: &str, after: &str) -> String {
+    let mut out = String::with_capacity(text.len() + ranges.len() * 32);
+    let mut ranges = ranges.iter().peekable();
+    let mut inside = false;
+    let mut last_escape = "";
+    let mut index = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let len = terminal::escape_len(rest);
+            out.push_str(&rest[..len]);
+            last_escape = &rest[..len];
+            if inside {
+                out.push_str(style);
+            }
+            rest = &rest[len..];
+            continue;
+        }
+        if inside && ranges.peek().is_some_and(|&&(_, end)| end == index) {
+            out.push_str(after);
+            out.push_str(last_escape);
+            inside = false;
+            ranges.next();
+        }
+        if !inside && ranges.peek().is_some_and(|&&(start, _)| start == index) {
+            out.push_str(style);
+            inside = true;
+        }
+        out.push(c);
+        index += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+    if inside {
+        out.push_str(after);
+        out.push_str(last_escape);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invisibles;
+
+    // Overlay `ranges` of `line` onto `shown`, the way print_line does once the line has
+    // had its tabs expanded or replaced by markers
+    fn overlay_shown(
+        line: &str,
+        shown: &str,
+        tab_width: Option<usize>,
+        markers: bool,
+        tabs_only: bool,
+        ranges: &[(usize, usize)],
+    ) -> String {
+        let offsets = invisibles::display_offsets(line, tab_width, markers, tabs_only);
+        let ranges: Vec<(usize, usize)> = ranges
+            .iter()
+            .map(|&(start, end)| (offsets[start], offsets[end]))
+            .collect();
+        overlay(shown, &ranges, "[", "]")
+    }
+
+    #[test]
+    fn match_ranges_counts_characters_and_merges_overlaps() {
+        let patterns = [Regex::new("b+").unwrap(), Regex::new("bc").unwrap()];
+        assert_eq!(match_ranges(&patterns, "äbbc bc\n"), vec![(1, 4), (5, 7)]);
+    }
+
+    #[test]
+    fn overlay_skips_escapes_and_restores_the_colors() {
+        let text = "\x1b[31mab\x1b[32mcd\x1b[0m";
+        assert_eq!(
+            overlay(text, &[(1, 3)], "<", ">"),
+            "\x1b[31ma<b\x1b[32m<c>\x1b[32md\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn overlay_lines_up_with_tab_markers() {
+        // "a\tx" shown with -T as "a^Ix": the x is the fourth character shown
+        let shown = overlay_shown("a\tx\n", "a^Ix\n", None, true, true, &[(2, 3)]);
+        assert_eq!(shown, "a^I[x]\n");
+    }
+
+    #[test]
+    fn overlay_lines_up_with_control_character_markers() {
+        let shown = overlay_shown("\x01\tx", "^A^Ix", None, true, false, &[(2, 3)]);
+        assert_eq!(shown, "^A^I[x]");
+        // Only tabs are shown as markers with tabs_only, so the other control stays one
+        let shown = overlay_shown("\x01\tx", "\x01^Ix", None, true, true, &[(2, 3)]);
+        assert_eq!(shown, "\x01^I[x]");
+    }
+
+    #[test]
+    fn overlay_lines_up_with_expanded_tabs() {
+        let shown = overlay_shown("ab\tx\ty", "ab  x   y", Some(4), false, false, &[(3, 4)]);
+        assert_eq!(shown, "ab  [x]   y");
+        let shown = overlay_shown("ab\tx\ty", "ab  x   y", Some(4), false, false, &[(5, 6)]);
+        assert_eq!(shown, "ab  x   [y]");
+    }
+}
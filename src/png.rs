@@ -0,0 +1,280 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::{Rgba, RgbaImage, imageops};
+use syntect::highlighting::{Color, Style, Theme};
+
+use crate::svg;
+
+// Layout settings for PNG export
+pub struct PngOptions {
+    // TrueType/OpenType font file; a common system monospace font when None
+    pub font_path: Option<PathBuf>,
+    // Font size in pixels
+    pub font_size: f32,
+    pub line_numbers: bool,
+}
+
+// Space around the window, which also holds the drop shadow
+const MARGIN: u32 = 48;
+// Height of the title bar with the three window buttons
+const TITLE_BAR_HEIGHT: u32 = 36;
+// Space between the window edge and the code
+const PADDING: u32 = 20;
+const CORNER_RADIUS: u32 = 10;
+const SHADOW_OFFSET: i64 = 12;
+const SHADOW_BLUR: f32 = 14.0;
+// Backdrop color behind the window
+const BACKDROP: Rgba<u8> = Rgba([0xab, 0xb8, 0xc3, 0xff]);
+
+// Monospace fonts tried, in order, when no --png-font is given
+const FALLBACK_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "/System/Library/Fonts/Monaco.ttf",
+    "C:\\Windows\\Fonts\\consola.ttf",
+];
+
+// Rasterize highlighted lines (each with its line number) into a window-style screenshot
+// and save it as a PNG at `path`
+pub fn save_png(
+    path: &Path,
+    theme: &Theme,
+    lines: &[(usize, Vec<(Style, String)>)],
+    options: &PngOptions,
+) -> Result<(), String> {
+    let font = load_font(options.font_path.as_deref())?;
+    let image = render(&font, theme, lines, options);
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+// Load the requested font, or the first fallback font that exists
+fn load_font(path: Option<&Path>) -> Result<FontVec, String> {
+    let candidates: Vec<&Path> = match path {
+        Some(path) => vec![path],
+        None => FALLBACK_FONTS.iter().map(Path::new).collect(),
+    };
+    for candidate in candidates {
+        if let Ok(bytes) = fs::read(candidate) {
+            return FontVec::try_from_vec(bytes)
+                .map_err(|_| format!("{} is not a usable font", candidate.display()));
+        }
+    }
+    Err(match path {
+        Some(path) => format!("failed to read font {}", path.display()),
+        None => "no monospace font found; pass one with --png-font".to_string(),
+    })
+}
+
+// Draw the backdrop, shadow, window and code
+fn render(
+    font: &FontVec,
+    theme: &Theme,
+    lines: &[(usize, Vec<(Style, String)>)],
+    options: &PngOptions,
+) -> RgbaImage {
+    let scaled = font.as_scaled(PxScale::from(options.font_size));
+    let advance = scaled.h_advance(font.glyph_id('M'));
+    let line_height = (scaled.height() + scaled.line_gap()).ceil().max(1.0);
+
+    let background = to_rgba(theme.settings.background.unwrap_or(Color::WHITE));
+    let foreground = theme.settings.foreground.unwrap_or(Color::BLACK);
+    let gutter = theme.settings.gutter_foreground.unwrap_or(Color {
+        a: 0x99,
+        ..foreground
+    });
+
+    // Expand tabs so every character sits on the monospace grid
+    let lines: Vec<(usize, Vec<(Style, String)>)> = lines
+        .iter()
+        .map(|(number, ranges)| (*number, svg::expand_line(ranges)))
+        .collect();
+    let gutter_columns = if options.line_numbers {
+        lines
+            .iter()
+            .map(|(n, _)| *n)
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len()
+            + 2
+    } else {
+        0
+    };
+    let columns = lines
+        .iter()
+        .map(|(_, ranges)| ranges.iter().map(|(_, t)| t.chars().count()).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        + gutter_columns;
+
+    let window_width = (columns as f32 * advance).ceil() as u32 + 2 * PADDING;
+    let window_height =
+        TITLE_BAR_HEIGHT + (lines.len() as f32 * line_height).ceil() as u32 + 2 * PADDING;
+    let width = window_width + 2 * MARGIN;
+    let height = window_height + 2 * MARGIN;
+
+    let mut image = RgbaImage::from_pixel(width, height, BACKDROP);
+
+    // Soft shadow: a dark window-shaped shape, blurred and offset down
+    let mut shadow = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+    fill_rounded_rect(
+        &mut shadow,
+        MARGIN,
+        MARGIN,
+        window_width,
+        window_height,
+        Rgba([0, 0, 0, 0x80]),
+    );
+    let shadow = imageops::blur(&shadow, SHADOW_BLUR);
+    imageops::overlay(&mut image, &shadow, 0, SHADOW_OFFSET);
+
+    // The window itself, with red/yellow/green buttons in the title bar
+    fill_rounded_rect(
+        &mut image,
+        MARGIN,
+        MARGIN,
+        window_width,
+        window_height,
+        background,
+    );
+    let button_y = (MARGIN + TITLE_BAR_HEIGHT / 2) as i64;
+    let buttons = [
+        Rgba([0xff, 0x5f, 0x56, 0xff]),
+        Rgba([0xff, 0xbd, 0x2e, 0xff]),
+        Rgba([0x27, 0xc9, 0x3f, 0xff]),
+    ];
+    for (index, color) in buttons.into_iter().enumerate() {
+        let x = (MARGIN + PADDING) as i64 + index as i64 * 20;
+        fill_circle(&mut image, x, button_y, 6, color);
+    }
+
+    // The code, one character cell at a time
+    let origin_x = (MARGIN + PADDING) as f32;
+    let origin_y = (MARGIN + TITLE_BAR_HEIGHT + PADDING) as f32;
+    for (row, (line_number, ranges)) in lines.iter().enumerate() {
+        let baseline = origin_y + row as f32 * line_height + scaled.ascent();
+        let mut column = 0;
+        if options.line_numbers {
+            let number = format!("{:>width$}  ", line_number, width = gutter_columns - 2);
+            for c in number.chars() {
+                draw_char(
+                    &mut image,
+                    font,
+                    &scaled,
+                    c,
+                    origin_x + column as f32 * advance,
+                    baseline,
+                    gutter,
+                );
+                column += 1;
+            }
+        }
+        for (style, text) in ranges {
+            for c in text.chars() {
+                draw_char(
+                    &mut image,
+                    font,
+                    &scaled,
+                    c,
+                    origin_x + column as f32 * advance,
+                    baseline,
+                    style.foreground,
+                );
+                column += 1;
+            }
+        }
+    }
+    image
+}
+
+// Rasterize one character with its baseline at (x, baseline), blending it onto the image
+fn draw_char(
+    image: &mut RgbaImage,
+    font: &FontVec,
+    scaled: &ab_glyph::PxScaleFont<&FontVec>,
+    c: char,
+    x: f32,
+    baseline: f32,
+    color: Color,
+) {
+    if c.is_whitespace() {
+        return;
+    }
+    let glyph = font
+        .glyph_id(c)
+        .with_scale_and_position(scaled.scale(), ab_glyph::point(x, baseline));
+    let Some(outline) = font.outline_glyph(glyph) else {
+        return;
+    };
+    let bounds = outline.px_bounds();
+    outline.draw(|gx, gy, coverage| {
+        let px = bounds.min.x as i64 + gx as i64;
+        let py = bounds.min.y as i64 + gy as i64;
+        let alpha = coverage * (color.a as f32 / 255.0);
+        blend(image, px, py, to_rgba(color), alpha);
+    });
+}
+
+// Blend `color` onto the pixel at (x, y) with the given opacity, ignoring out-of-range pixels
+fn blend(image: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>, alpha: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+        return;
+    }
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        let under = pixel[channel] as f32;
+        let over = color[channel] as f32;
+        pixel[channel] = (under + (over - under) * alpha.clamp(0.0, 1.0)).round() as u8;
+    }
+    pixel[3] = pixel[3].max((alpha * 255.0) as u8);
+}
+
+// Fill a rectangle with rounded corners
+fn fill_rounded_rect(
+    image: &mut RgbaImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    color: Rgba<u8>,
+) {
+    let radius = CORNER_RADIUS.min(width / 2).min(height / 2) as i64;
+    for py in y..y + height {
+        for px in x..x + width {
+            // Distance into the corner square, if this pixel is in one
+            let dx = (radius - (px - x) as i64 - 1).max((px - x) as i64 - (width as i64 - radius));
+            let dy = (radius - (py - y) as i64 - 1).max((py - y) as i64 - (height as i64 - radius));
+            if dx > 0 && dy > 0 && dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+            if px < image.width() && py < image.height() {
+                image.put_pixel(px, py, color);
+            }
+        }
+    }
+}
+
+// Fill a circle centered at (cx, cy)
+fn fill_circle(image: &mut RgbaImage, cx: i64, cy: i64, radius: i64, color: Rgba<u8>) {
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            // Anti-alias the edge by how far the pixel center lies outside the circle
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let alpha = (radius as f32 + 0.5 - distance).clamp(0.0, 1.0);
+            if alpha > 0.0 {
+                blend(image, cx + dx, cy + dy, color, alpha);
+            }
+        }
+    }
+}
+
+fn to_rgba(color: Color) -> Rgba<u8> {
+    Rgba([color.r, color.g, color.b, 0xff])
+}
@@ -0,0 +1,159 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use glob::Pattern;
+
+use crate::detect::{parse_pattern, path_matches};
+use crate::error::CathError;
+
+// A --preprocessor hook: files whose path matches `pattern` are read as the output of
+// `command`, which gets the file's path in place of "%s" (or after it, without one)
+#[derive(Clone, Debug)]
+pub struct Hook {
+    pattern: Pattern,
+    command: String,
+}
+
+impl Hook {
+    // Parse "GLOB:COMMAND", e.g. "*.plist:plutil -p" or "*.sqlite:sqlite3 %s .dump"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (pattern, command) = value
+            .split_once(':')
+            .filter(|(pattern, command)| !pattern.is_empty() && !command.trim().is_empty())
+            .ok_or_else(|| format!("expected GLOB:COMMAND, got '{}'", value))?;
+        Ok(Hook {
+            pattern: parse_pattern(pattern)?,
+            command: command.trim().to_string(),
+        })
+    }
+
+    // Whether the hook is for the file at `path`
+    pub fn matches(&self, path: &Path) -> bool {
+        path_matches(&self.pattern, &path.to_string_lossy().replace('\\', "/"))
+    }
+
+    // Run the hook's command on `path` and return its output. A command that fails (or a
+    // path that can't be given to one) is a notice, and the file is read as it is instead
+    pub fn run(&self, path: &Path) -> Result<Vec<u8>, CathError> {
+        let quoted = path
+            .to_str()
+            .and_then(|path| shlex::try_quote(path).ok())
+            .ok_or_else(|| {
+                CathError::notice(
+                    Some(path),
+                    format!("can't be passed to preprocessor '{}'", self.command),
+                )
+            })?
+            .into_owned();
+        let command = if self.command.contains("%s") {
+            self.command.replace("%s", &quoted)
+        } else {
+            format!("{} {}", self.command, quoted)
+        };
+        match shell(&command).stderr(Stdio::inherit()).output() {
+            Ok(output) if output.status.success() => Ok(output.stdout),
+            Ok(output) => Err(CathError::notice(
+                Some(path),
+                format!("preprocessor '{}' failed ({})", self.command, output.status),
+            )),
+            Err(err) => Err(CathError::notice(
+                Some(path),
+                format!("failed to run preprocessor '{}': {}", self.command, err),
+            )),
+        }
+    }
+}
+
+// An input preprocessor, set up the way less's LESSOPEN is: "|command %s" reads the
+// command's output in place of the file (the file itself is read when there's no output,
+// or with "||" when the command fails), and "command %s" prints the name of a file to read
+// instead, which the close command is given along with the original to clean up after
+pub struct Preprocessor {
+    open: String,
+    close: Option<String>,
+}
+
+impl Preprocessor {
+    // The preprocessor set with CATH_OPEN (and CATH_CLOSE), or less's, from LESSOPEN (and
+    // LESSCLOSE), when `lessopen` says to use it. less's is opt-in, since distributions
+    // set it up for viewing in a pager rather than highlighting
+    pub fn from_env(lessopen: bool) -> Option<Self> {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        if let Some(open) = var("CATH_OPEN") {
+            return Some(Preprocessor {
+                open,
+                close: var("CATH_CLOSE"),
+            });
+        }
+        lessopen
+            .then(|| var("LESSOPEN"))
+            .flatten()
+            .map(|open| Preprocessor {
+                open,
+                close: var("LESSCLOSE"),
+            })
+    }
+
+    // Run the preprocessor on `path` and return what to read in its place, or None when
+    // the file is to be read as it is. Preprocessors for stdin ("|-command") don't apply,
+    // since the input is a file here
+    pub fn run(&self, path: &Path) -> Option<Vec<u8>> {
+        let quoted = shlex::try_quote(path.to_str()?).ok()?.into_owned();
+        if let Some(command) = self.open.strip_prefix('|') {
+            let (command, strict) = match command.strip_prefix('|') {
+                Some(command) => (command, true),
+                None => (command, false),
+            };
+            let command = command.strip_prefix('-').unwrap_or(command);
+            let output = shell(&command.replacen("%s", &quoted, 1))
+                .stderr(Stdio::inherit())
+                .output()
+                .ok()?;
+            if strict {
+                return output.status.success().then_some(output.stdout);
+            }
+            return (!output.stdout.is_empty()).then_some(output.stdout);
+        }
+
+        let output = shell(&self.open.replacen("%s", &quoted, 1))
+            .stderr(Stdio::inherit())
+            .output()
+            .ok()?;
+        let replacement = String::from_utf8(output.stdout).ok()?;
+        let replacement = replacement.trim_end_matches(['\n', '\r']);
+        if replacement.is_empty() {
+            return None;
+        }
+        let data = fs::read(replacement).ok();
+        if let Some(close) = &self.close
+            && let Ok(replacement) = shlex::try_quote(replacement)
+        {
+            let close = close
+                .replacen("%s", &quoted, 1)
+                .replacen("%s", &replacement, 1);
+            let _ = shell(&close).status();
+        }
+        data
+    }
+}
+
+// A command that runs `command` with the system shell
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command).stdin(Stdio::null());
+    shell
+}
@@ -0,0 +1,237 @@
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use crate::assets::{self, HighlightingAssets};
+use crate::detect::find_syntax_by_language;
+use crate::error::CathError;
+use crate::hyperlink::HyperlinkChoice;
+use crate::line_range::{Line, LineRange};
+use crate::preview::ImageProtocol;
+use crate::printer;
+use crate::terminal::{AnsiFilter, ColorChoice, ColorDepth, TerminalWidth};
+use crate::{Args, settle_args};
+
+// Prints files the way the cath command does, for other programs to embed:
+//
+//     PrettyPrinter::new()
+//         .input_file("src/main.rs")
+//         .theme("base16-ocean.dark")
+//         .line_numbers(true)
+//         .print()?;
+//
+// The output can also go to any writer with print_to, or be kept with print_to_string.
+// It starts out from cath's defaults, without the config file or environment variables
+// that only apply to the command line, and with colors on. Unlike the command, it doesn't
+// look at the terminal unless asked to with detect_terminal, so the output is the same
+// wherever it goes. Notices about the inputs, like a binary file being left out, aren't
+// errors and aren't returned
+pub struct PrettyPrinter {
+    args: Args,
+    detect_terminal: bool,
+}
+
+impl PrettyPrinter {
+    pub fn new() -> Self {
+        let mut args = Args::parse_from(["cath"]);
+        args.color = ColorChoice::Always;
+        // Decorations asked for are drawn wherever the output goes
+        args.force_colorization = true;
+        PrettyPrinter {
+            args,
+            detect_terminal: false,
+        }
+    }
+
+    // Add a file to print; "-" stands for stdin
+    pub fn input_file(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.args.file_paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    // Add several files to print, in order
+    pub fn input_files<P: AsRef<Path>>(&mut self, paths: impl IntoIterator<Item = P>) -> &mut Self {
+        for path in paths {
+            self.input_file(path);
+        }
+        self
+    }
+
+    // Highlight every input as `language` (a syntax name or file extension) instead of
+    // detecting it
+    pub fn language(&mut self, language: &str) -> &mut Self {
+        self.args.language = Some(language.to_string());
+        self
+    }
+
+    // The name of the color theme to highlight with
+    pub fn theme(&mut self, theme: &str) -> &mut Self {
+        self.args.explicit_theme = Some(theme.to_string());
+        self
+    }
+
+    pub fn line_numbers(&mut self, yes: bool) -> &mut Self {
+        self.args.style.numbers = yes;
+        self
+    }
+
+    // The file name above each file
+    pub fn header(&mut self, yes: bool) -> &mut Self {
+        self.args.style.header = yes;
+        self
+    }
+
+    // The lines between the gutter and the text, and under headers
+    pub fn grid(&mut self, yes: bool) -> &mut Self {
+        self.args.style.grid = yes;
+        self
+    }
+
+    // A horizontal line between files
+    pub fn rule(&mut self, yes: bool) -> &mut Self {
+        self.args.style.rule = yes;
+        self
+    }
+
+    // Go by the terminal the way the cath command does: a theme to suit its background
+    // (which asks the terminal, when printing to one), the colors COLORTERM and TERM say it
+    // has, and images in the protocol it supports. Otherwise the default dark theme is used
+    // with 24-bit colors, and images are shown as hex dumps
+    pub fn detect_terminal(&mut self, yes: bool) -> &mut Self {
+        self.detect_terminal = yes;
+        self
+    }
+
+    // Whether to write colors at all; without them the output is plain text
+    pub fn colored_output(&mut self, yes: bool) -> &mut Self {
+        self.args.color = if yes {
+            ColorChoice::Always
+        } else {
+            ColorChoice::Never
+        };
+        self
+    }
+
+    // Expand tabs to the next multiple of `width` columns
+    pub fn tab_width(&mut self, width: u16) -> &mut Self {
+        self.args.tabs = Some(width.max(1));
+        self
+    }
+
+    // Lay the output out for a terminal `columns` wide, soft-wrapping long lines
+    pub fn term_width(&mut self, columns: usize) -> &mut Self {
+        self.args.terminal_width = Some(TerminalWidth::Columns(columns));
+        self
+    }
+
+    // Only print lines `start` to `end` (counted from 1) of each input; can be given more
+    // than once
+    pub fn line_range(&mut self, start: usize, end: usize) -> &mut Self {
+        self.args.line_ranges.push(LineRange {
+            start: Line::Start(start.max(1)),
+            end: Line::Start(end),
+        });
+        self
+    }
+
+    // Emphasize line `line` with the theme's line highlight color
+    pub fn highlight(&mut self, line: usize) -> &mut Self {
+        self.args.highlight_lines.push(LineRange {
+            start: Line::Start(line),
+            end: Line::Start(line),
+        });
+        self
+    }
+
+    // Print the inputs (stdin when none were added) to stdout. An input that can't be read
+    // doesn't stop the ones after it from being printed; the first such error is returned
+    // once they have been
+    pub fn print(&self) -> Result<(), CathError> {
+        let stdout = io::stdout();
+        let is_terminal = stdout.is_terminal();
+        self.write(&mut stdout.lock(), is_terminal)
+    }
+
+    // Print the inputs to `writer`, e.g. a TUI's buffer. Long lines only wrap with a
+    // term_width, since there's no terminal to go by
+    pub fn print_to(&self, writer: &mut impl Write) -> Result<(), CathError> {
+        self.write(writer, false)
+    }
+
+    // Print the inputs into a String, with color escapes unless colored_output(false)
+    // was given
+    pub fn print_to_string(&self) -> Result<String, CathError> {
+        let mut buffer = Vec::new();
+        self.write(&mut buffer, false)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    // Print the inputs to `writer`, laid out as for a terminal when `is_terminal` is set
+    fn write(&self, writer: &mut dyn Write, is_terminal: bool) -> Result<(), CathError> {
+        let mut args = self.args.clone();
+        if !self.detect_terminal {
+            args.explicit_theme
+                .get_or_insert_with(|| args.theme_dark.clone());
+            if args.color_depth == ColorDepth::Auto {
+                args.color_depth = ColorDepth::TrueColor;
+            }
+            if args.image_protocol == ImageProtocol::Auto {
+                args.image_protocol = ImageProtocol::Never;
+            }
+        }
+        let colored = settle_args(&mut args, is_terminal);
+
+        let assets = HighlightingAssets::load();
+        let needs_syntaxes = !args.plain || args.language.is_some() || args.style.header_language;
+        let plain_text_set;
+        let ps = if needs_syntaxes {
+            assets.syntax_set()
+        } else {
+            plain_text_set = assets::plain_text_syntax_set();
+            &plain_text_set
+        };
+        let theme = assets
+            .theme_set
+            .themes
+            .get(&args.theme)
+            .ok_or_else(|| CathError::UnknownTheme(args.theme.clone()))?;
+        let forced_syntax = match args.language.as_deref() {
+            Some(language) => Some(
+                find_syntax_by_language(ps, language)
+                    .ok_or_else(|| CathError::UnknownLanguage(language.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let inputs = if args.file_paths.is_empty() {
+            vec![PathBuf::from("-")]
+        } else {
+            args.file_paths.clone()
+        };
+        let mut handle = BufWriter::new(
+            AnsiFilter::new(writer, !colored)
+                .keep_hyperlinks(args.hyperlinks == HyperlinkChoice::Always),
+        );
+        let mut first_error = None;
+        printer::print_inputs(
+            &mut handle,
+            &args,
+            &inputs,
+            ps,
+            theme,
+            forced_syntax,
+            &mut |err| {
+                first_error.get_or_insert(err);
+            },
+        );
+        handle.flush().map_err(CathError::Output)?;
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
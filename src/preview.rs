@@ -0,0 +1,262 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+
+use crate::terminal;
+
+// How images are shown inline in the terminal
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageProtocol {
+    // Pick a protocol the terminal supports, judging by the environment
+    Auto,
+    // The kitty graphics protocol (kitty, Ghostty)
+    Kitty,
+    // iTerm2's inline images (iTerm2, WezTerm)
+    #[value(name = "iterm2")]
+    ITerm2,
+    // Sixel graphics (foot, mlterm, xterm with sixels enabled)
+    Sixel,
+    // Never show images, leaving them to the hex dump
+    Never,
+}
+
+impl ImageProtocol {
+    // Replace Auto with the protocol the terminal is known to support, or Never when it
+    // isn't known to support any. Output that isn't going to a terminal gets no images
+    // Terminals that aren't recognized by their environment are asked whether they do
+    // sixels, but only when `probe` is set, since that takes a round trip to the terminal
+    pub fn resolve(self, is_terminal: bool, probe: bool) -> Self {
+        if !is_terminal {
+            return ImageProtocol::Never;
+        }
+        if self != ImageProtocol::Auto {
+            return self;
+        }
+        let var = |name| env::var(name).unwrap_or_default();
+        // tmux doesn't pass graphics through without extra configuration
+        if env::var_os("TMUX").is_some() {
+            return ImageProtocol::Never;
+        }
+        if var("TERM") == "xterm-kitty"
+            || env::var_os("KITTY_WINDOW_ID").is_some()
+            || var("TERM_PROGRAM") == "ghostty"
+        {
+            return ImageProtocol::Kitty;
+        }
+        if matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm")
+            || var("LC_TERMINAL") == "iTerm2"
+        {
+            return ImageProtocol::ITerm2;
+        }
+        // Sixels are encoded from the decoded image, so builds without the image feature
+        // can't draw them and go on to the next choice (and skip asking the terminal)
+        if cfg!(feature = "image")
+            && (var("TERM").starts_with("foot")
+                || var("TERM").starts_with("mlterm")
+                || env::var_os("MLTERM").is_some()
+                || (probe && terminal::supports_sixel()))
+        {
+            return ImageProtocol::Sixel;
+        }
+        ImageProtocol::Never
+    }
+}
+
+// The image formats that are shown inline
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl ImageFormat {
+    // The format of the image `block` (the start of an input) holds, by its signature
+    pub fn detect(block: &[u8]) -> Option<Self> {
+        if block.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if block.starts_with(b"\xff\xd8\xff") {
+            Some(ImageFormat::Jpeg)
+        } else if block.starts_with(b"GIF87a") || block.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+}
+
+// Whether the file at `path` is an image that would be shown inline
+pub fn is_image_file(path: &Path) -> bool {
+    let mut block = [0; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut block))
+        .is_ok_and(|_| ImageFormat::detect(&block).is_some())
+}
+
+// Size assumed for a terminal cell when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+// Write `data`, an image in `format`, as an inline image scaled down to fit the terminal
+// (small images are kept at their size). Returns false when the image can't be shown with
+// `protocol`, so the caller can fall back to a hex dump
+pub fn print_image(
+    handle: &mut dyn Write,
+    data: &[u8],
+    format: ImageFormat,
+    protocol: ImageProtocol,
+) -> io::Result<bool> {
+    let (columns, cell_width) = fit(dimensions(data, format));
+    match protocol {
+        ImageProtocol::Kitty => {
+            // kitty only takes PNG as is; other formats have to be converted first
+            let png = match format {
+                ImageFormat::Png => data.to_vec(),
+                _ => match to_png(data) {
+                    Some(png) => png,
+                    None => return Ok(false),
+                },
+            };
+            write_kitty(handle, &png, columns)?;
+        }
+        ImageProtocol::ITerm2 => {
+            write!(
+                handle,
+                "\x1b]1337;File=inline=1;size={};width={};preserveAspectRatio=1:{}\x07",
+                data.len(),
+                columns,
+                STANDARD.encode(data)
+            )?;
+        }
+        ImageProtocol::Sixel => match to_sixel(data, columns * cell_width) {
+            Some(sixel) => write!(handle, "{}", sixel)?,
+            None => return Ok(false),
+        },
+        ImageProtocol::Auto | ImageProtocol::Never => return Ok(false),
+    }
+    writeln!(handle)?;
+    Ok(true)
+}
+
+// Send a PNG with the kitty graphics protocol, which takes the data in chunks of at most
+// 4096 base64 bytes; the height follows from the width and the aspect ratio
+fn write_kitty(handle: &mut dyn Write, png: &[u8], columns: u32) -> io::Result<()> {
+    let encoded = STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            write!(handle, "\x1b_Ga=T,f=100,q=2,c={},m={};", columns, more)?;
+        } else {
+            write!(handle, "\x1b_Gm={};", more)?;
+        }
+        handle.write_all(chunk)?;
+        write!(handle, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+// The number of columns to show an image of `size` pixels in: its own size in cells, but
+// no wider than the terminal and no taller than what fits on the screen. The width of a
+// cell in pixels comes with it, for protocols that are given the size in pixels
+fn fit(size: Option<(u32, u32)>) -> (u32, u32) {
+    // The window's columns and rows, and its width and height in pixels
+    #[cfg(not(target_arch = "wasm32"))]
+    let window = crossterm::terminal::window_size().ok().map(|window| {
+        (
+            u32::from(window.columns),
+            u32::from(window.rows),
+            u32::from(window.width),
+            u32::from(window.height),
+        )
+    });
+    // wasm builds have no terminal to ask
+    #[cfg(target_arch = "wasm32")]
+    let window: Option<(u32, u32, u32, u32)> = None;
+    // A terminal that reports no size at all is taken to be the classic 80x24
+    let window = window.filter(|&(columns, rows, _, _)| columns > 0 && rows > 0);
+    let (term_columns, term_rows) =
+        window.map_or((80, 24), |(columns, rows, _, _)| (columns, rows));
+    let (cell_width, cell_height) = window
+        .filter(|&(_, _, width, height)| width > 0 && height > 0)
+        .map_or(DEFAULT_CELL_SIZE, |(columns, rows, width, height)| {
+            (width / columns, height / rows)
+        });
+    let Some((width, height)) = size.filter(|&(width, height)| width > 0 && height > 0) else {
+        return (term_columns, cell_width);
+    };
+    let mut columns = width.div_ceil(cell_width.max(1)).clamp(1, term_columns);
+    // Leave a row for the prompt (or the next file's header) below the image
+    let max_rows = term_rows.saturating_sub(1).max(1);
+    let rows =
+        (columns * cell_width) as u64 * height as u64 / width as u64 / cell_height.max(1) as u64;
+    if rows > max_rows as u64 {
+        columns = ((columns as u64 * max_rows as u64 / rows) as u32).max(1);
+    }
+    (columns, cell_width)
+}
+
+// The width and height in pixels of an image, read from its header
+fn dimensions(data: &[u8], format: ImageFormat) -> Option<(u32, u32)> {
+    let be32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let be16 = |at: usize| {
+        Some(u32::from(u16::from_be_bytes(
+            data.get(at..at + 2)?.try_into().ok()?,
+        )))
+    };
+    let le16 = |at: usize| {
+        Some(u32::from(u16::from_le_bytes(
+            data.get(at..at + 2)?.try_into().ok()?,
+        )))
+    };
+    match format {
+        // The IHDR chunk always comes first
+        ImageFormat::Png => Some((be32(16)?, be32(20)?)),
+        // The logical screen descriptor follows the signature
+        ImageFormat::Gif => Some((le16(6)?, le16(8)?)),
+        // JPEG keeps the size in its start-of-frame segment, which comes after any number of
+        // other segments
+        ImageFormat::Jpeg => {
+            let mut at = 2;
+            while *data.get(at)? == 0xff {
+                let marker = *data.get(at + 1)?;
+                let len = be16(at + 2)? as usize;
+                // SOF0 through SOF15, except the DHT, JPG and DAC markers in between
+                if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+                    return Some((be16(at + 7)?, be16(at + 5)?));
+                }
+                at += 2 + len;
+            }
+            None
+        }
+    }
+}
+
+// Convert an image to PNG, which needs the image crate to decode it
+#[cfg(feature = "image")]
+fn to_png(data: &[u8]) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(data).ok()?;
+    let mut png = io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).ok()?;
+    Some(png.into_inner())
+}
+
+#[cfg(not(feature = "image"))]
+fn to_png(_data: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+// Encode an image as sixels `width` pixels wide, which also needs it decoded
+#[cfg(feature = "image")]
+fn to_sixel(data: &[u8], width: u32) -> Option<String> {
+    crate::sixel::encode(data, width)
+}
+
+#[cfg(not(feature = "image"))]
+fn to_sixel(_data: &[u8], _width: u32) -> Option<String> {
+    None
+}
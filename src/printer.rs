@@ -0,0 +1,1420 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rayon::prelude::*;
+use regex::Regex;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Style, Theme};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::archive;
+use crate::detect::detect_syntax;
+use crate::error::CathError;
+use crate::git::{self, BlameLine, LineChanges};
+use crate::highlight::Tokenizer;
+use crate::hyperlink::{self, HyperlinkChoice};
+use crate::invisibles::{self, Piece};
+use crate::json;
+use crate::line_range::{self, Line, LineRange};
+use crate::location::Location;
+use crate::markdown::{self, MarkdownOptions};
+use crate::net;
+use crate::notebook;
+use crate::overstrike;
+use crate::pattern;
+use crate::preview::{self, ImageFormat, ImageProtocol};
+use crate::query;
+use crate::reformat::{self, Format};
+use crate::render::{RenderMode, Renderer};
+use crate::table;
+use crate::terminal::{self, WrapMode};
+use crate::tokens;
+use crate::{
+    Args, follow, hexdump, highlight_lines, html, input, input_options, requested_ranges,
+    syntax_for_file_name, too_large_to_highlight, within_highlight_budget,
+};
+
+// The decorations drawn around the text, picked with --style (or the older flags for
+// single components, like --line-numbers)
+#[derive(Clone, Copy, Default)]
+pub struct Decorations {
+    // Line numbers in the gutter
+    pub numbers: bool,
+    // A vertical line between the gutter and the text, and a horizontal one under headers
+    pub grid: bool,
+    // The file name above each file
+    pub header: bool,
+    // The file's size, modification time and detected language, added to the header
+    pub header_size: bool,
+    pub header_modified: bool,
+    pub header_language: bool,
+    // A horizontal line between files
+    pub rule: bool,
+    // A separator where lines are left out between ranges or hunks
+    pub snip: bool,
+    // Git modification markers in the gutter
+    pub changes: bool,
+}
+
+impl Decorations {
+    // Every component, for --style full
+    fn full() -> Self {
+        Decorations {
+            numbers: true,
+            grid: true,
+            header: true,
+            header_size: true,
+            header_modified: true,
+            header_language: true,
+            rule: true,
+            snip: true,
+            changes: true,
+        }
+    }
+
+    // Parse a comma-separated list of components, e.g. "numbers,header"; the presets
+    // "full" and "plain" turn on all components and none of them
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut decorations = Decorations::default();
+        for component in value.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            match component {
+                "full" => decorations = Decorations::full(),
+                "plain" => decorations = Decorations::default(),
+                "numbers" => decorations.numbers = true,
+                "grid" => decorations.grid = true,
+                "header" => decorations.header = true,
+                // The header details only make sense with the header itself
+                "header-size" => {
+                    decorations.header = true;
+                    decorations.header_size = true;
+                }
+                "header-modified" => {
+                    decorations.header = true;
+                    decorations.header_modified = true;
+                }
+                "header-language" => {
+                    decorations.header = true;
+                    decorations.header_language = true;
+                }
+                "rule" => decorations.rule = true,
+                "snip" => decorations.snip = true,
+                "changes" => decorations.changes = true,
+                _ => {
+                    return Err(format!(
+                        "unknown style component '{}' (expected numbers, grid, header, header-size, header-modified, header-language, rule, snip, changes, full or plain)",
+                        component
+                    ));
+                }
+            }
+        }
+        Ok(decorations)
+    }
+}
+
+// Print every input in order, one after another. An input that fails is handed to `report`
+// in its place, and the ones after it are still printed; notices about the inputs are
+// handed to it too
+pub fn print_inputs(
+    handle: &mut impl Write,
+    args: &Args,
+    inputs: &[PathBuf],
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+    report: &mut dyn FnMut(CathError),
+) {
+    if inputs.len() == 1 {
+        let printed = print_input_with_header(
+            handle,
+            args,
+            0,
+            &inputs[0],
+            ps,
+            theme,
+            forced_syntax,
+            report,
+        );
+        if let Err(err) = printed {
+            report(err);
+        }
+        return;
+    }
+
+    // Several files are highlighted in parallel into buffers, which are then written out in
+    // order. Working through them a batch at a time keeps memory bounded and lets the first
+    // files show up before the last ones are done. Notices are held on to with the buffers
+    let batch_size = rayon::current_num_threads() * 2;
+    for (batch, chunk) in inputs.chunks(batch_size).enumerate() {
+        let buffers: Vec<_> = chunk
+            .par_iter()
+            .enumerate()
+            .map(|(offset, input)| {
+                let mut buffer = Vec::new();
+                let mut notices = Vec::new();
+                let index = batch * batch_size + offset;
+                let result = print_input_with_header(
+                    &mut buffer,
+                    args,
+                    index,
+                    input,
+                    ps,
+                    theme,
+                    forced_syntax,
+                    &mut |notice| notices.push(notice),
+                );
+                (buffer, notices, result)
+            })
+            .collect();
+        for (buffer, notices, result) in buffers {
+            notices.into_iter().for_each(&mut *report);
+            // What was printed before the error goes out first, so they're seen in order
+            let result = result.and(
+                handle
+                    .write_all(&buffer)
+                    .and_then(|_| handle.flush())
+                    .map_err(CathError::Output),
+            );
+            if let Err(err) = result {
+                report(err);
+            }
+        }
+    }
+}
+
+// Print the input at position `index` of the file list, preceded by its header if enabled
+#[allow(clippy::too_many_arguments)]
+fn print_input_with_header(
+    handle: &mut impl Write,
+    args: &Args,
+    index: usize,
+    path: &Path,
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+    report: &mut dyn FnMut(CathError),
+) -> Result<(), CathError> {
+    // Treat "-" as a request to read from standard input
+    let file_path = Some(path).filter(|path| *path != Path::new("-"));
+    // --tail starts reading files near their end, instead of going through all of them
+    let options = input::InputOptions {
+        tail: args.tail,
+        ..input_options(args)
+    };
+    let mut input = input::open_input(file_path, &options)?;
+    std::mem::take(&mut input.notices)
+        .into_iter()
+        .for_each(&mut *report);
+    // A --file-name for the input picks the syntax like a real file name would
+    let forced_syntax = forced_syntax.or_else(|| syntax_for_file_name(args, index, ps));
+
+    let decoration = decoration_color(args, theme);
+    let width = terminal::width(args.terminal_width);
+    // The grid frames each file, with its horizontal lines meeting the vertical one after
+    // the gutter; the rule between files is then already there
+    let framed = args.style.grid && !args.to_html;
+    let junction = Gutter::new(args, None).width();
+    let frame_line = |handle: &mut dyn Write, joint: char| {
+        write_rule(
+            handle,
+            &decoration,
+            width,
+            Some((junction, joint)).filter(|_| junction > 0),
+        )
+    };
+    // Consecutive files are set apart with a rule, or else a blank line before a header
+    if index > 0 && !args.to_html && !framed {
+        if args.style.rule {
+            write_rule(handle, &decoration, width, None).map_err(CathError::Output)?;
+        } else if args.style.header {
+            writeln!(handle).map_err(CathError::Output)?;
+        }
+    }
+    if framed {
+        frame_line(handle, '┬').map_err(CathError::Output)?;
+    }
+    if args.style.header {
+        let mut name = args
+            .file_names
+            .get(index)
+            .map(PathBuf::as_path)
+            .or(file_path)
+            .map_or("STDIN".into(), |path| path.display().to_string());
+        if args.show_bom
+            && let Some(encoding) = input.bom
+        {
+            name.push_str(&format!(" ({} BOM)", encoding.name()));
+        }
+        let details = header_details(args, file_path, &mut input, ps, forced_syntax);
+        if args.to_html {
+            let title = if details.is_empty() {
+                name
+            } else {
+                format!("{} ({})", name, details.join(", "))
+            };
+            write!(handle, "{}", html::file_header(&title)).map_err(CathError::Output)?;
+        } else {
+            // Inside the frame the header sits right of the vertical line, like the text
+            if framed && junction > 0 {
+                write!(handle, "{:junction$}{}│\x1b[0m ", "", decoration)
+                    .map_err(CathError::Output)?;
+            }
+            write!(handle, "\x1b[1m{}\x1b[0m", name).map_err(CathError::Output)?;
+            if !details.is_empty() {
+                write!(handle, "  {}{}\x1b[0m", decoration, details.join(" · "))
+                    .map_err(CathError::Output)?;
+            }
+            writeln!(handle).map_err(CathError::Output)?;
+            if framed {
+                frame_line(handle, '┼').map_err(CathError::Output)?;
+            } else {
+                write_rule(handle, &decoration, width, None).map_err(CathError::Output)?;
+            }
+        }
+    }
+
+    // A "file:line:column" argument opens the file at that line
+    let location = file_path.and_then(|path| {
+        args.locations
+            .iter()
+            .find(|(file, _)| file == path)
+            .map(|&(_, location)| location)
+    });
+    print_input(
+        handle,
+        args,
+        file_path,
+        input,
+        ps,
+        theme,
+        forced_syntax,
+        location,
+        report,
+    )?;
+    if framed {
+        frame_line(handle, '┴').map_err(CathError::Output)?;
+    }
+    Ok(())
+}
+
+// Read a single input (a file, or stdin when `file_path` is None) and write it to `handle`,
+// centered on `location` when there is one
+#[allow(clippy::too_many_arguments)]
+fn print_input(
+    handle: &mut impl Write,
+    args: &Args,
+    file_path: Option<&Path>,
+    input: input::Input,
+    ps: &SyntaxSet,
+    theme: &Theme,
+    forced_syntax: Option<&SyntaxReference>,
+    location: Option<Location>,
+    report: &mut dyn FnMut(CathError),
+) -> Result<(), CathError> {
+    let read_error = |err| CathError::io(file_path, err);
+    // An archive without a member shows what's in it, instead of its compressed bytes
+    if let Some(entries) = &input.listing {
+        return archive::write_listing(handle, entries, true).map_err(CathError::Output);
+    }
+    // Read the input a line at a time, so large files start printing right away and
+    // memory use doesn't grow with the file; the first line is read up front because
+    // syntax detection may need it
+    let mut reader = input.reader;
+    // Images are shown inline on terminals that can display them
+    if !args.hex
+        && args.image_protocol != ImageProtocol::Never
+        && let Some(format) = reader.fill_buf().ok().and_then(ImageFormat::detect)
+    {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(read_error)?;
+        if preview::print_image(handle, &data, format, args.image_protocol)
+            .map_err(CathError::Output)?
+        {
+            return Ok(());
+        }
+        reader = Box::new(io::Cursor::new(data));
+    }
+    // Binary data has no lines to highlight, so it's shown as a hex dump instead
+    if args.hex || input::is_binary(&mut reader, args.lossy).map_err(read_error)? {
+        return hexdump::print_hexdump(handle, &mut reader).map_err(read_error);
+    }
+    let mut line = String::new();
+    // Bytes read so far, which is where following the file picks up
+    let mut offset = input.skipped
+        + input::read_line(&mut reader, &mut line, args.lossy).map_err(read_error)? as u64;
+
+    // Pick the syntax for this input (unless one was forced with --language) and create
+    // a fresh highlighter, so that parse state never leaks from one file into the next
+    // Files too large to highlight in reasonable time are printed plain instead
+    let plain = args.plain || too_large_to_highlight(args, file_path);
+    let mut syntax = forced_syntax.unwrap_or_else(|| {
+        detect_syntax(
+            ps,
+            file_path,
+            &peek_head(&line, &mut reader),
+            input.content_type.as_deref(),
+            &args.map_syntax,
+        )
+    });
+    let mut h = (!plain).then(|| HighlightLines::new(syntax, theme));
+
+    // Documents are rendered instead of shown as source when asked to, and by default for
+    // the formats that have a renderer when the output is colored
+    let renderer = match args.render {
+        RenderMode::Auto if plain => None,
+        mode => mode.renderer(syntax, file_path),
+    }
+    .filter(|_| !args.follow && !args.to_html && !args.tokens);
+    if let Some(renderer) = renderer {
+        let first_line_len = line.len();
+        let mut content = line;
+        input::read_to_string(&mut reader, &mut content, args.lossy).map_err(read_error)?;
+        let options = MarkdownOptions {
+            width: terminal::width(args.terminal_width),
+            color_depth: args.color_depth,
+            hyperlinks: args.hyperlinks == HyperlinkChoice::Always,
+        };
+        let rendered = match renderer {
+            Renderer::Markdown => Some(markdown::render(&content, ps, theme, &options)),
+            Renderer::Notebook => notebook::render(&content, ps, theme, &options),
+            Renderer::Table => table::render(
+                &content,
+                args.delimiter
+                    .unwrap_or_else(|| table::delimiter_for(file_path, &content)),
+                options.width,
+            ),
+        };
+        if let Some(rendered) = rendered {
+            write!(handle, "{}", rendered).map_err(CathError::Output)?;
+            return Ok(());
+        }
+        // Inputs that turn out not to be in the format (e.g. a broken notebook) are shown
+        // as they are after all
+        let rest = content.split_off(first_line_len);
+        line = content;
+        reader = Box::new(io::Cursor::new(rest.into_bytes()));
+    }
+
+    // A --query is run on JSON inputs, and its results are shown in their place. Dense
+    // JSON (or XML, with --format xml) is spread out over indented lines before it's
+    // highlighted; inputs without a name to go by (like an API response piped in) are
+    // recognized by their content
+    let reformat = if args.query.is_some() {
+        Some(Format::Json)
+    } else {
+        args.format
+            .or(args.pretty_json.then_some(Format::Json))
+            .filter(|format| format.matches(syntax, forced_syntax.is_some(), &line))
+    };
+    let mut reformatted = false;
+    if !args.follow
+        && let Some(format) = reformat
+    {
+        let first_line_len = line.len();
+        let mut content = line;
+        input::read_to_string(&mut reader, &mut content, args.lossy).map_err(read_error)?;
+        let result = match &args.query {
+            Some(query) => match query::run(query, &content) {
+                Ok(result) => Some(result),
+                Err(err) => return Err(CathError::input(file_path, err)),
+            },
+            None => format.reformat(&content, syntax),
+        };
+        let split = match result {
+            Some(pretty) => {
+                content = pretty;
+                reformatted = true;
+                if forced_syntax.is_none()
+                    && let Some(reformatted) =
+                        ps.find_syntax_by_name(format.syntax_name(syntax, &content))
+                {
+                    syntax = reformatted;
+                    h = (!plain).then(|| HighlightLines::new(syntax, theme));
+                }
+                content.find('\n').map_or(content.len(), |end| end + 1)
+            }
+            None => first_line_len,
+        };
+        let rest = content.split_off(split);
+        line = content;
+        reader = Box::new(io::Cursor::new(rest.into_bytes()));
+    } else if reformat.is_none()
+        && reformat::is_markup(syntax)
+        && terminal::display_width(&line) > terminal::width(args.terminal_width)
+        && reader.fill_buf().is_ok_and(|rest| rest.is_empty())
+        && io::stderr().is_terminal()
+    {
+        // A whole document on one line is all but unreadable, so point out how to fix that
+        report(CathError::notice(
+            file_path,
+            "hint: it's all on a single line; --format xml indents it",
+        ));
+    }
+
+    // JSON lines are shown a record at a time, each spread out over indented lines next to
+    // the number of the line it's on, with a rule between records
+    let json_lines = !reformatted
+        && !plain
+        && !args.to_html
+        && !args.tokens
+        && args.render != RenderMode::Never
+        && forced_syntax.is_none_or(|syntax| syntax.name == "JSON")
+        && json::is_json_lines(file_path, &peek_head(&line, &mut reader));
+    if json_lines && let Some(json) = ps.find_syntax_by_name("JSON") {
+        h = Some(HighlightLines::new(json, theme));
+    }
+
+    // Some options need to go through the input before it's printed; files are simply read
+    // twice, and stdin (or a URL, or reformatted JSON) is kept in memory for the second pass
+    let in_memory = file_path.is_none_or(|path| reformatted || net::is_url(path));
+    let mut scan_ahead = |scan: &mut dyn FnMut(&mut dyn BufRead) -> io::Result<usize>| {
+        match file_path.filter(|_| !in_memory) {
+            Some(path) => scan(&mut input::open_input(Some(path), &input_options(args))?.reader),
+            None => {
+                let mut rest = Vec::new();
+                reader.read_to_end(&mut rest).map_err(read_error)?;
+                let scanned = scan(&mut line.as_bytes().chain(rest.as_slice()));
+                reader = Box::new(io::Cursor::new(rest));
+                scanned
+            }
+        }
+        .map_err(read_error)
+    };
+
+    // --find shows the first line that matches in the middle of the lines around it, in
+    // place of any requested ranges, and so does a location for the line it's at
+    let found = match &args.find {
+        _ if let Some(location) = location => Some(location.line),
+        Some(pattern) => {
+            let found = scan_ahead(&mut |reader| first_match(reader, pattern, args.lossy))?;
+            if found == 0 {
+                report(CathError::notice(
+                    file_path,
+                    format!("no match for '{}'", pattern),
+                ));
+                return Ok(());
+            }
+            report(CathError::notice(
+                file_path,
+                format!("found on line {}", found),
+            ));
+            Some(found)
+        }
+        None => None,
+    };
+    // --grep only shows the lines that match (or with --invert-match, the ones that
+    // don't), and the context around them
+    let grep_lines = match &args.grep {
+        Some(pattern) => {
+            let mut matches = Vec::new();
+            scan_ahead(&mut |reader| {
+                matches = matching_lines(reader, pattern, args.invert_match, args.lossy)?;
+                Ok(matches.len())
+            })?;
+            let before = args.before_context.or(args.context).unwrap_or(0);
+            let after = args.after_context.or(args.context).unwrap_or(0);
+            let mut lines = HashSet::new();
+            for line_number in matches {
+                lines.extend(line_number.saturating_sub(before).max(1)..=line_number + after);
+            }
+            Some(lines)
+        }
+        None => None,
+    };
+
+    // Ranges counted from the end need the number of lines, so the input is gone through
+    // once up front for them. Ranges counted from the start don't depend on it
+    let requested = if let Some(line_number) = found {
+        let context = args.context.unwrap_or(5);
+        vec![LineRange {
+            start: Line::Start(line_number.saturating_sub(context).max(1)),
+            end: Line::Start(line_number + context),
+        }]
+    } else if input.tailed {
+        args.line_ranges.clone()
+    } else {
+        requested_ranges(args)
+    };
+    let total_lines = if line_range::counts_from_end(&requested)
+        || line_range::counts_from_end(&args.highlight_lines)
+    {
+        scan_ahead(&mut |reader| input::count_lines(reader))?
+    } else {
+        0
+    };
+    let line_ranges = line_range::resolve(&requested, total_lines);
+
+    // An input opened at its last lines by --tail is numbered from the line it starts at,
+    // which takes counting the lines skipped, so that's only done when the numbers are shown
+    // or needed to match up git information
+    let numbered = args.style.numbers
+        || args.style.changes
+        || args.diff
+        || args.blame
+        || !args.highlight_lines.is_empty();
+    let skipped_lines = match file_path {
+        Some(path) if input.tailed && numbered => File::open(path)
+            .and_then(|file| input::count_lines(&mut io::BufReader::new(file.take(input.skipped))))
+            .map_err(read_error)?,
+        _ => 0,
+    };
+
+    // Reading stops after the last line of the requested ranges
+    let end = line_range::last_line(&line_ranges);
+
+    // --tokens writes the spans each line is highlighted with instead of the line itself
+    if args.tokens {
+        let syntax = if plain {
+            ps.find_syntax_plain_text()
+        } else {
+            syntax
+        };
+        let mut tokenizer = Tokenizer::new(file_path, syntax, ps, theme);
+        let mut content = line;
+        input::read_to_string(&mut reader, &mut content, args.lossy).map_err(read_error)?;
+        for (index, text) in LinesWithEndings::from(&content).enumerate() {
+            let line_number = index + 1;
+            if line_number > end {
+                break;
+            }
+            // The lines before a range are still parsed, for the state they leave behind
+            let spans = tokenizer.line(text, args.strict_highlighting)?;
+            if line_range::in_ranges(&line_ranges, line_number) {
+                tokens::write_line_record(handle, file_path, line_number, &spans)
+                    .map_err(CathError::Output)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // HTML export renders the highlighted lines as markup instead of terminal escapes
+    if args.to_html {
+        let syntax = if plain {
+            ps.find_syntax_plain_text()
+        } else {
+            syntax
+        };
+        let mut content = line;
+        input::read_to_string(&mut reader, &mut content, args.lossy).map_err(read_error)?;
+        let lines = highlight_lines(
+            &content,
+            file_path,
+            syntax,
+            ps,
+            theme,
+            &line_ranges,
+            args.strict_highlighting,
+        )?;
+        write!(
+            handle,
+            "{}",
+            html::render_pre(theme, &lines, args.style.numbers)
+        )
+        .map_err(CathError::Output)?;
+        return Ok(());
+    }
+
+    // Look up git information when the gutter columns are wanted
+    let gutter = Gutter::new(args, file_path);
+    let mut format = LineFormat::new(args, file_path, ps, theme, gutter, total_lines);
+    // The line --find found is emphasized like a --highlight-line, and so is the line of a
+    // location, with the character at its column shown in reverse video
+    if let Some(line_number) = found {
+        format.emphasized.push(LineRange {
+            start: Line::Start(line_number),
+            end: Line::Start(line_number),
+        });
+    }
+    format.target = location.and_then(|location| Some((location.line, location.column?)));
+
+    // With --diff, only changed lines and their surrounding context are shown
+    let diff_lines = if args.diff {
+        format.gutter.changes.as_ref().map(|changes| {
+            let mut lines = HashSet::new();
+            for &line_number in changes.keys() {
+                let first = line_number.saturating_sub(args.diff_context).max(1);
+                lines.extend(first..=line_number + args.diff_context);
+            }
+            lines
+        })
+    } else {
+        None
+    };
+    let is_visible = |line_number: usize| {
+        line_range::in_ranges(&line_ranges, line_number)
+            && diff_lines
+                .as_ref()
+                .is_none_or(|lines| lines.contains(&line_number))
+            && grep_lines
+                .as_ref()
+                .is_none_or(|lines| lines.contains(&line_number))
+    };
+
+    // Print the requested lines of the input
+    let mut line_count = skipped_lines;
+    let mut last_printed = None;
+    let mut squeeze = Squeeze::new(args);
+    while !line.is_empty() {
+        line_count += 1;
+        let squeezed = squeeze.skip(&line);
+        if squeezed {
+            // Squeezed lines aren't a gap between hunks
+            last_printed = last_printed.map(|_| line_count);
+        }
+        if is_visible(line_count) && !squeezed {
+            // Mark the lines skipped between two ranges or hunks
+            if let Some(last) = last_printed
+                && last + 1 != line_count
+                && args.style.snip
+            {
+                format
+                    .write_snip(handle, line_count - last - 1)
+                    .map_err(CathError::Output)?;
+            }
+            if json_lines {
+                let rule = last_printed == Some(line_count - 1);
+                print_record(handle, args, h.as_mut(), &format, &line, line_count, rule)?;
+            } else {
+                print_line(handle, args, h.as_mut(), &format, &line, line_count)?;
+            }
+            last_printed = Some(line_count);
+        } else if let Some(h) = &mut h
+            && (diff_lines.is_some() || grep_lines.is_some() || squeezed)
+            && within_highlight_budget(&line, args.strict_highlighting)
+        {
+            // Keep the highlighter in sync across hidden lines, so that a hunk starting
+            // inside e.g. a block comment (or a blank line in a string) is still colored
+            // correctly
+            h.highlight_line(&line, ps)
+                .map_err(|err| CathError::highlight(file_path, err))?;
+        }
+        // Nothing after the end of the range is shown, so stop reading there (unless
+        // following, which needs to know where the file ends)
+        if line_count >= end && !args.follow {
+            break;
+        }
+        line.clear();
+        offset += input::read_line(&mut reader, &mut line, args.lossy).map_err(read_error)? as u64;
+    }
+
+    // In follow mode, keep printing lines appended to the file with the same highlighter,
+    // so that multi-line constructs carry over from the initial content
+    if args.follow
+        && let Some(path) = file_path
+    {
+        follow::follow(handle, path, offset, args.lossy, |handle, line| {
+            line_count += 1;
+            if !squeeze.skip(line) && is_visible(line_count) {
+                if json_lines {
+                    print_record(handle, args, h.as_mut(), &format, line, line_count, true)?;
+                } else {
+                    print_line(handle, args, h.as_mut(), &format, line, line_count)?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    if !args.plain {
+        // Reset terminal colors so they don't bleed into a following file or the prompt
+        write!(handle, "\x1b[0m").map_err(CathError::Output)?;
+    }
+    Ok(())
+}
+
+// The number of the first line in `reader` that `pattern` matches, or 0 when none does
+fn first_match(reader: &mut dyn BufRead, pattern: &Regex, lossy: bool) -> io::Result<usize> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if input::read_line(reader, &mut line, lossy)? == 0 {
+            return Ok(0);
+        }
+        line_number += 1;
+        if pattern.is_match(line.trim_end_matches(['\n', '\r'])) {
+            return Ok(line_number);
+        }
+    }
+}
+
+// The numbers of the lines in `reader` that `pattern` matches, or doesn't match when
+// `invert` is set
+fn matching_lines(
+    reader: &mut dyn BufRead,
+    pattern: &Regex,
+    invert: bool,
+    lossy: bool,
+) -> io::Result<Vec<usize>> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut matches = Vec::new();
+    loop {
+        line.clear();
+        if input::read_line(reader, &mut line, lossy)? == 0 {
+            return Ok(matches);
+        }
+        line_number += 1;
+        if pattern.is_match(line.trim_end_matches(['\n', '\r'])) != invert {
+            matches.push(line_number);
+        }
+    }
+}
+
+// Tracks runs of empty lines for --squeeze-blank and --squeeze-limit
+struct Squeeze {
+    // Most empty lines kept in a row, or None when not squeezing
+    limit: Option<usize>,
+    // Empty lines seen in a row so far
+    run: usize,
+}
+
+impl Squeeze {
+    fn new(args: &Args) -> Self {
+        Squeeze {
+            limit: args.squeeze_limit.or(args.squeeze_blank.then_some(1)),
+            run: 0,
+        }
+    }
+
+    // Whether `line` is an empty line past the limit, which should be dropped
+    fn skip(&mut self, line: &str) -> bool {
+        let Some(limit) = self.limit else {
+            return false;
+        };
+        if line.trim_end_matches(['\n', '\r']).is_empty() {
+            self.run += 1;
+            self.run > limit
+        } else {
+            self.run = 0;
+            false
+        }
+    }
+}
+
+// The columns printed to the left of each line
+struct Gutter {
+    // Git modification markers, when --changes is on
+    changes: Option<LineChanges>,
+    // Git blame annotations (one per line), when --blame is on
+    blame: Option<Vec<BlameLine>>,
+    line_numbers: bool,
+    // With --number-nonblank, blank lines get no number and the others are counted in
+    // `nonblank_lines` instead of by their position in the file
+    number_nonblank: bool,
+    nonblank_lines: Cell<usize>,
+    // Draw a vertical line after the columns, when there are any
+    grid: bool,
+    // Where line numbers link to, with "{line}" still to be filled in, when they're
+    // written as hyperlinks
+    line_link: Option<String>,
+    // Set while writing the lines a JSON lines record is spread over after its first,
+    // which get blank columns
+    continued: Cell<bool>,
+}
+
+impl Gutter {
+    // The gutter for the columns asked for, with the git information for `file_path`;
+    // stdin and files outside a repository still get the (empty) columns so the output
+    // stays aligned. --diff needs the changes anyway, so it also turns on their markers
+    fn new(args: &Args, file_path: Option<&Path>) -> Self {
+        Gutter {
+            changes: (args.style.changes || args.diff)
+                .then(|| file_path.and_then(git::line_changes).unwrap_or_default()),
+            blame: args
+                .blame
+                .then(|| file_path.and_then(git::blame).unwrap_or_default()),
+            line_numbers: args.style.numbers,
+            number_nonblank: args.number_nonblank,
+            grid: args.style.grid,
+            nonblank_lines: Cell::new(0),
+            line_link: file_path
+                .filter(|_| args.hyperlinks == HyperlinkChoice::Always)
+                .and_then(|path| hyperlink::line_link_format(&args.hyperlink_format, path)),
+            continued: Cell::new(false),
+        }
+    }
+
+    // Width of the columns in terminal columns, which is where the grid line goes (longer
+    // line numbers push it further right, but that's rare enough to not be worth a pass
+    // over the input)
+    fn width(&self) -> usize {
+        let mut width = 0;
+        if self.changes.is_some() {
+            width += 2;
+        }
+        if self.blame.is_some() {
+            width += BlameLine::blank_annotation().len() + 1;
+        }
+        if self.number_nonblank {
+            // Six digits, then a tab to the next tab stop
+            width = ((width + 6) / 8 + 1) * 8;
+        } else if self.line_numbers {
+            width += 5;
+        }
+        width
+    }
+
+    // Write the gutter columns for `line`, which is line `line_number` of the input: change
+    // marker, blame annotation, line number, and the grid line drawn in `decoration`
+    fn write(
+        &self,
+        handle: &mut dyn Write,
+        decoration: &str,
+        line_number: usize,
+        line: &str,
+    ) -> io::Result<()> {
+        if self.continued.get() {
+            write!(handle, "{:1$}", "", self.width())?;
+            if self.grid && self.width() > 0 {
+                write!(handle, "{}│\x1b[0m ", decoration)?;
+            }
+            return Ok(());
+        }
+        // Unchanged lines get a blank marker
+        if let Some(changes) = &self.changes {
+            match changes.get(&line_number) {
+                Some(change) => write!(handle, "{} ", change.marker())?,
+                None => write!(handle, "  ")?,
+            }
+        }
+        if let Some(blame) = &self.blame {
+            match blame.get(line_number - 1) {
+                Some(blame_line) => write!(handle, "{} ", blame_line.annotation())?,
+                None => write!(handle, "{} ", BlameLine::blank_annotation())?,
+            }
+        }
+        if self.number_nonblank {
+            // The format matches cat -b exactly, including blank lines getting no number
+            // column at all, so its output can be compared or parsed the same way
+            if !line.trim_end_matches(['\n', '\r']).is_empty() {
+                self.nonblank_lines.set(self.nonblank_lines.get() + 1);
+                self.write_number(handle, self.nonblank_lines.get(), line_number, 6)?;
+                write!(handle, "\t")?;
+            }
+        } else if self.line_numbers {
+            self.write_number(handle, line_number, line_number, 4)?;
+            write!(handle, " ")?;
+        }
+        let has_columns = self.changes.is_some()
+            || self.blame.is_some()
+            || self.number_nonblank
+            || self.line_numbers;
+        if self.grid && has_columns {
+            write!(handle, "{}│\x1b[0m ", decoration)?;
+        }
+        Ok(())
+    }
+
+    // Write `number` right-aligned in `width` columns, as a link to line `line_number` of
+    // the file when there's a link format (only the digits are clickable, not the padding)
+    fn write_number(
+        &self,
+        handle: &mut dyn Write,
+        number: usize,
+        line_number: usize,
+        width: usize,
+    ) -> io::Result<()> {
+        let digits = number.to_string();
+        let padding = width.saturating_sub(digits.len());
+        match &self.line_link {
+            Some(link) => {
+                let target = link.replace("{line}", &line_number.to_string());
+                write!(
+                    handle,
+                    "{:padding$}{}{}{}",
+                    "",
+                    hyperlink::osc8(&target),
+                    digits,
+                    hyperlink::osc8("")
+                )
+            }
+            None => write!(handle, "{:padding$}{}", "", digits),
+        }
+    }
+}
+
+// Everything print_line needs for an input besides the line itself
+struct LineFormat<'a> {
+    // The input (None for stdin), for errors highlighting its lines
+    file_path: Option<&'a Path>,
+    ps: &'a SyntaxSet,
+    gutter: Gutter,
+    // Escape sequence starting the markers drawn by --show-all and --show-ends
+    invisibles: String,
+    // Terminal width to soft-wrap long lines at, if they're wrapped
+    wrap_width: Option<usize>,
+    // Terminal width to cut long lines off at, with --chop-long-lines
+    chop_width: Option<usize>,
+    // Escape sequence for the background of lines picked with --highlight-line
+    emphasis: String,
+    // The lines picked with --highlight-line, counted from the start of the input
+    emphasized: Vec<LineRange>,
+    // Escape sequence for the colors of --pattern matches
+    match_style: String,
+    // The line and column a "file:line:column" argument points at
+    target: Option<(usize, usize)>,
+}
+
+impl<'a> LineFormat<'a> {
+    fn new(
+        args: &Args,
+        file_path: Option<&'a Path>,
+        ps: &'a SyntaxSet,
+        theme: &Theme,
+        gutter: Gutter,
+        total_lines: usize,
+    ) -> Self {
+        let invisibles = decoration_color(args, theme);
+        let wrap_width =
+            (args.wrap != WrapMode::Never).then(|| terminal::width(args.terminal_width));
+        // Themes without a line highlight color get a gray that works on light and dark
+        // backgrounds alike
+        let emphasis = terminal::background_escape(
+            theme.settings.line_highlight.unwrap_or(Color {
+                r: 0x80,
+                g: 0x80,
+                b: 0x80,
+                a: 0xff,
+            }),
+            args.color_depth,
+        );
+        // Matches take the theme's colors for search results, or else black on yellow
+        let match_style = format!(
+            "{}{}",
+            terminal::background_escape(
+                theme.settings.find_highlight.unwrap_or(Color {
+                    r: 0xff,
+                    g: 0xd7,
+                    b: 0x00,
+                    a: 0xff,
+                }),
+                args.color_depth,
+            ),
+            terminal::foreground_escape(
+                theme
+                    .settings
+                    .find_highlight_foreground
+                    .unwrap_or(Color::BLACK),
+                args.color_depth,
+            )
+        );
+        LineFormat {
+            file_path,
+            ps,
+            gutter,
+            invisibles,
+            wrap_width,
+            chop_width: args
+                .chop_long_lines
+                .then(|| terminal::width(args.terminal_width)),
+            emphasis,
+            emphasized: line_range::resolve(&args.highlight_lines, total_lines),
+            match_style,
+            target: None,
+        }
+    }
+
+    // Write the separator that stands in for `omitted` lines left out of the output, in
+    // the same subdued color as the invisibles
+    fn write_snip(&self, handle: &mut dyn Write, omitted: usize) -> io::Result<()> {
+        let lines = if omitted == 1 { "line" } else { "lines" };
+        writeln!(
+            handle,
+            "{}--- 8< --- {} {} omitted ---\x1b[0m",
+            self.invisibles, omitted, lines
+        )
+    }
+}
+
+// The details about an input that --style asks to show in its header. The language is
+// detected the same way as for highlighting, from a peek at the start of the input that
+// leaves it to be read in full afterwards
+fn header_details(
+    args: &Args,
+    file_path: Option<&Path>,
+    input: &mut input::Input,
+    ps: &SyntaxSet,
+    forced_syntax: Option<&SyntaxReference>,
+) -> Vec<String> {
+    let mut details = Vec::new();
+    let metadata = file_path.and_then(|path| fs::metadata(path).ok());
+    if args.style.header_size
+        && let Some(metadata) = &metadata
+    {
+        details.push(format_size(metadata.len()));
+    }
+    if args.style.header_modified
+        && let Some(modified) = metadata.and_then(|metadata| metadata.modified().ok())
+        && let Ok(since_epoch) = modified.duration_since(UNIX_EPOCH)
+    {
+        details.push(format!(
+            "modified {}",
+            format_time(since_epoch.as_secs() as i64)
+        ));
+    }
+    if args.style.header_language {
+        let binary = input::is_binary(&mut input.reader, args.lossy).unwrap_or(false);
+        let language = if args.hex || binary {
+            "binary".to_string()
+        } else {
+            let head = peek_head("", &mut input.reader);
+            forced_syntax
+                .unwrap_or_else(|| {
+                    detect_syntax(
+                        ps,
+                        file_path,
+                        &head,
+                        input.content_type.as_deref(),
+                        &args.map_syntax,
+                    )
+                })
+                .name
+                .clone()
+        };
+        details.push(language);
+    }
+    details
+}
+
+// The start of an input for syntax detection: the lines read so far, then a peek at what's
+// buffered after them (at most a few KiB), which is still there to be read afterwards
+fn peek_head(read: &str, reader: &mut dyn BufRead) -> String {
+    let block = reader.fill_buf().unwrap_or_default();
+    let block = &block[..block.len().min(4096)];
+    format!("{}{}", read, String::from_utf8_lossy(block))
+}
+
+// A file size in bytes, or in binary units once it's a KiB or more, e.g. "4.2 MiB"
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+// Format a Unix timestamp as a UTC "YYYY-MM-DD HH:MM" time
+pub fn format_time(timestamp: i64) -> String {
+    let minutes = timestamp.rem_euclid(86_400) / 60;
+    format!(
+        "{} {:02}:{:02} UTC",
+        git::format_date(timestamp),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+// The escape sequence for decorations and invisibles. Themes have no color for them as
+// such, so the subdued gutter or indent guide color is used, and dim text when the theme
+// has neither
+fn decoration_color(args: &Args, theme: &Theme) -> String {
+    theme
+        .settings
+        .gutter_foreground
+        .or(theme.settings.guide)
+        .map_or("\x1b[2m".to_string(), |color| {
+            terminal::foreground_escape(color, args.color_depth)
+        })
+}
+
+// Write a horizontal line across the terminal, in the `decoration` color
+// With a `junction`, the character given there is drawn at that column instead, where the
+// line meets the grid's vertical one
+fn write_rule(
+    handle: &mut dyn Write,
+    decoration: &str,
+    width: usize,
+    junction: Option<(usize, char)>,
+) -> io::Result<()> {
+    let line: String = (0..width)
+        .map(|column| match junction {
+            Some((at, joint)) if at == column => joint,
+            _ => '─',
+        })
+        .collect();
+    writeln!(handle, "{}{}\x1b[0m", decoration, line)
+}
+
+// Write one line of output, preceded by its gutter, with syntax highlighting unless there's
+// no highlighter (plain output)
+fn print_line(
+    handle: &mut dyn Write,
+    args: &Args,
+    h: Option<&mut HighlightLines>,
+    format: &LineFormat,
+    line: &str,
+    line_number: usize,
+) -> Result<(), CathError> {
+    // The gutter is kept as text, since wrapped lines need to know how wide it is
+    let mut gutter = Vec::new();
+    format
+        .gutter
+        .write(&mut gutter, &format.invisibles, line_number, line)
+        .map_err(CathError::Output)?;
+    handle.write_all(&gutter).map_err(CathError::Output)?;
+    let gutter = String::from_utf8_lossy(&gutter);
+    // Man pages (as man writes them for a pager) make text bold or underlined the way a
+    // printer would, by overstriking it. The plain text is what gets highlighted, with the
+    // emphasis put back as terminal styles afterwards
+    let overstruck;
+    let (line, emphasis) = if line.contains('\x08') {
+        overstruck = overstrike::parse(line);
+        (overstruck.0.as_str(), Some(&overstruck.1))
+    } else {
+        (line, None)
+    };
+    // Tabs are expanded before highlighting, so the highlighted text lines up with what the
+    // terminal shows whatever its tab stops are
+    let show_tabs = args.show_all || args.show_tabs;
+    let raw = line;
+    let expanded;
+    let line = match args.tabs {
+        Some(width) if !show_tabs => {
+            expanded = invisibles::expand_tabs(line, width as usize);
+            expanded.as_str()
+        }
+        _ => line,
+    };
+    // In plain mode (or for a line too long to highlight), the line is written without
+    // syntax highlighting
+    let ranges: Option<Vec<(Style, &str)>> = match h {
+        // Highlight the line and get back a vector of (Style, text) pairs
+        Some(h) if within_highlight_budget(line, args.strict_highlighting) => Some(
+            h.highlight_line(line, format.ps)
+                .map_err(|err| CathError::highlight(format.file_path, err))?,
+        ),
+        _ => None,
+    };
+    // Convert the styled ranges to ANSI escape codes for terminal colors
+    let escape =
+        |ranges: &[(Style, &str)]| terminal::as_terminal_escaped(ranges, false, args.color_depth);
+    let text = if show_tabs {
+        // Nonprinting characters (or just tabs) in the line's content are swapped for their
+        // markers; the line ending gets its markers below
+        let content_len = line.trim_end_matches(['\n', '\r']).len();
+        let plain = [(Style::default(), line)];
+        let pieces = invisibles::split_nonprinting(
+            ranges.as_deref().unwrap_or(&plain),
+            content_len,
+            !args.show_all,
+        );
+        pieces
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Text(style, text) if ranges.is_some() => escape(&[(style, text)]),
+                Piece::Text(_, text) => text.to_string(),
+                // Only the marker's color is reset, keeping any --highlight-line background
+                Piece::Marker(marker) => format!("{}{}\x1b[22;39m", format.invisibles, marker),
+            })
+            .collect()
+    } else {
+        ranges.as_deref().map_or_else(|| line.to_string(), escape)
+    };
+    let text = match emphasis {
+        Some(emphasis) => overstrike::restyle(&text, emphasis),
+        None => text,
+    };
+    // Emphasized lines get their background behind the text and the markers, extended to
+    // the edge of the terminal with \x1b[K
+    let emphasized = format
+        .emphasized
+        .iter()
+        .any(|range| range.contains(line_number));
+    // Matches of --pattern and the column of a file:line:column argument are found in the
+    // line as it is, and moved to where they're shown once tabs are expanded or shown as
+    // markers
+    let offsets = invisibles::display_offsets(
+        raw,
+        args.tabs.map(|width| width as usize),
+        show_tabs,
+        !args.show_all,
+    );
+    let display = |index: usize| offsets.get(index).copied().unwrap_or(usize::MAX);
+    // Matches of --pattern stand out on top of the syntax colors, with the line's own
+    // background back after each of them
+    let matches: Vec<(usize, usize)> = pattern::match_ranges(&args.patterns, raw)
+        .into_iter()
+        .map(|(start, end)| (display(start), display(end)))
+        .collect();
+    let text = if matches.is_empty() {
+        text
+    } else {
+        let after = if emphasized {
+            format.emphasis.as_str()
+        } else {
+            "\x1b[49m"
+        };
+        pattern::overlay(&text, &matches, &format.match_style, after)
+    };
+    let text = match format.target {
+        Some((target, column)) if target == line_number => {
+            let cell = (display(column - 1), display(column));
+            pattern::overlay(&text, &[cell], "\x1b[7m", "\x1b[27m")
+        }
+        _ => text,
+    };
+    // --columns cuts the line down once it's styled, so the colors are those of the whole line
+    let text = match &args.columns {
+        Some(columns) => columns.apply(&text),
+        None => text,
+    };
+
+    // With --show-ends, the line ending is replaced by markers: ^M for a carriage return,
+    // then $ and the newline (a last line without a newline gets neither)
+    let content = text.trim_end_matches(['\n', '\r']);
+    let mut body = if emphasized {
+        format!("{}{}", format.emphasis, content)
+    } else {
+        content.to_string()
+    };
+    let ending = if args.show_ends {
+        if line.ends_with("\r\n") || line.ends_with('\r') {
+            body.push_str(&format!("{}^M\x1b[22;39m", format.invisibles));
+        }
+        if line.ends_with('\n') {
+            body.push_str(&format!("{}$\x1b[22;39m", format.invisibles));
+            "\n"
+        } else {
+            ""
+        }
+    } else {
+        &text[content.len()..]
+    };
+    if emphasized {
+        body.push_str("\x1b[K\x1b[0m");
+    }
+    if args.hyperlinks == HyperlinkChoice::Always {
+        body = hyperlink::link_urls(&body);
+    }
+
+    if let Some(width) = format.chop_width {
+        body = chop(
+            &body,
+            width,
+            terminal::display_width(&gutter),
+            &format.invisibles,
+        );
+    }
+
+    // Write the line to the buffered output
+    match format.wrap_width {
+        Some(width) => write_wrapped(handle, &body, width, terminal::display_width(&gutter)),
+        None => write!(handle, "{}", body),
+    }
+    .and_then(|_| write!(handle, "{}", ending))
+    .map_err(CathError::Output)
+}
+
+// Print a record of a JSON lines input, line `line_number`, spread out over indented lines
+// after a rule setting it apart from the record before (when `rule` is set). Only the first
+// of its lines gets the line's number; lines that aren't JSON are printed as they are
+fn print_record(
+    handle: &mut dyn Write,
+    args: &Args,
+    mut h: Option<&mut HighlightLines>,
+    format: &LineFormat,
+    line: &str,
+    line_number: usize,
+    rule: bool,
+) -> Result<(), CathError> {
+    let Some(record) = json::pretty(line) else {
+        return print_line(handle, args, h, format, line, line_number);
+    };
+    if rule {
+        // Inside the grid the rule meets the vertical line after the gutter
+        let junction = format.gutter.width();
+        let junction = Some((junction, '┼')).filter(|_| format.gutter.grid && junction > 0);
+        let width = terminal::width(args.terminal_width);
+        write_rule(handle, &format.invisibles, width, junction).map_err(CathError::Output)?;
+    }
+    for (index, record_line) in LinesWithEndings::from(&record).enumerate() {
+        format.gutter.continued.set(index > 0);
+        print_line(
+            handle,
+            args,
+            h.as_deref_mut(),
+            format,
+            record_line,
+            line_number,
+        )?;
+    }
+    format.gutter.continued.set(false);
+    Ok(())
+}
+
+// Cut `body`, the text of a line written after a gutter `indent` columns wide, off where it
+// would go past `width` columns, with a marker in the last column to show there's more.
+// The escapes past the cut are kept, so whatever they reset is still reset
+fn chop(body: &str, width: usize, indent: usize, marker_color: &str) -> String {
+    if indent + terminal::display_width(body) <= width || width <= indent + 1 {
+        return body.to_string();
+    }
+    let mut out = String::with_capacity(body.len());
+    let mut column = indent;
+    let mut chopped = false;
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' {
+            terminal::escape_len(rest)
+        } else {
+            let char_width = terminal::char_width(c, column);
+            if !chopped && column + char_width >= width {
+                out.push_str(&format!("{}…\x1b[22;39m", marker_color));
+                chopped = true;
+            }
+            column += char_width;
+            if chopped {
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            c.len_utf8()
+        };
+        out.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+    out
+}
+
+// Write `body`, which starts at column `indent` (after the gutter), breaking it onto new
+// lines at `width` columns; continuation lines are indented past the gutter so it stays
+// clear. Escape sequences are copied through untouched, so colors carry over the breaks
+fn write_wrapped(
+    handle: &mut dyn Write,
+    body: &str,
+    width: usize,
+    indent: usize,
+) -> io::Result<()> {
+    // With hardly any room left next to the gutter, wrapping would only make things worse
+    if width <= indent + 1 {
+        return write!(handle, "{}", body);
+    }
+    let mut column = indent;
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' {
+            terminal::escape_len(rest)
+        } else {
+            let char_width = terminal::char_width(c, column);
+            if column + char_width > width && column > indent {
+                write!(handle, "\n{:indent$}", "", indent = indent)?;
+                column = indent;
+            }
+            column += terminal::char_width(c, column);
+            c.len_utf8()
+        };
+        handle.write_all(&rest.as_bytes()[..len])?;
+        rest = &rest[len..];
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+#[cfg(not(target_arch = "wasm32"))]
+use jaq_interpret::{Ctx, Filter, FilterT, ParseCtx, RcIter, Val};
+#[cfg(not(target_arch = "wasm32"))]
+use serde_json::Value;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::json;
+
+// jaq's hash maps need a source of random numbers, which wasm builds don't have, so
+// --query isn't available in them
+#[cfg(target_arch = "wasm32")]
+const UNSUPPORTED: &str = "--query isn't supported in this build";
+
+// Check that a --query filter parses and only uses filters that exist, so a mistake in it
+// is reported before any input is read
+pub fn parse_query(query: &str) -> Result<String, String> {
+    compile(query).map(|_| query.to_string())
+}
+
+// Compile a jq filter, with jq's standard library of filters (map, select, keys, ...)
+// available to it
+#[cfg(not(target_arch = "wasm32"))]
+fn compile(query: &str) -> Result<Filter, String> {
+    let mut defs = ParseCtx::new(Vec::new());
+    defs.insert_natives(jaq_core::core());
+    defs.insert_defs(jaq_std::std());
+    let (main, errors) = jaq_parse::parse(query, jaq_parse::main());
+    if let Some(error) = errors.first() {
+        return Err(error.to_string());
+    }
+    let filter = defs.compile(main.ok_or("empty query")?);
+    match defs.errs.first() {
+        Some((error, _)) => Err(error.to_string()),
+        None => Ok(filter),
+    }
+}
+
+// Run `query` on each of the JSON values in `text` (one document, or a stream of them like
+// JSON lines), the way jq does, and return its results as indented JSON, one after another
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run(query: &str, text: &str) -> Result<String, String> {
+    let filter = compile(query)?;
+    let inputs = RcIter::new(core::iter::empty());
+    let mut out = String::new();
+    for value in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        let value = value.map_err(|err| format!("invalid JSON: {}", err))?;
+        for result in filter.run((Ctx::new([], &inputs), Val::from(value))) {
+            let result = Value::from(result.map_err(|err| err.to_string())?).to_string();
+            out.push_str(&json::pretty(&result).unwrap_or(result));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn compile(_query: &str) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn run(_query: &str, _text: &str) -> Result<String, String> {
+    Err(UNSUPPORTED.to_string())
+}
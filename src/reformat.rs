@@ -0,0 +1,54 @@
+use clap::ValueEnum;
+use syntect::parsing::SyntaxReference;
+
+use crate::json;
+use crate::xml;
+
+// Formats whose dense (e.g. minified or single-line) documents can be re-indented before
+// they're highlighted
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    // XML, and HTML
+    Xml,
+}
+
+impl Format {
+    // Whether an input detected as `syntax`, with `line` as its first line, is in this
+    // format. Inputs whose syntax wasn't forced are also recognized by their content, since
+    // a minified document piped in has no name to go by
+    pub fn matches(self, syntax: &SyntaxReference, forced: bool, line: &str) -> bool {
+        match self {
+            Format::Json => syntax.name == "JSON" || (!forced && json::looks_like_json(line)),
+            Format::Xml => is_markup(syntax) || (!forced && line.trim_start().starts_with('<')),
+        }
+    }
+
+    // Re-indent `text`, an input in this format detected as `syntax`. None if it can't
+    // be, so it's shown as it is
+    pub fn reformat(self, text: &str, syntax: &SyntaxReference) -> Option<String> {
+        match self {
+            Format::Json => json::pretty(text),
+            Format::Xml => Some(xml::pretty(text, is_html(syntax, text))),
+        }
+    }
+
+    // The name of the syntax to highlight a reformatted input detected as `syntax` and
+    // holding `text` with
+    pub fn syntax_name(self, syntax: &SyntaxReference, text: &str) -> &'static str {
+        match self {
+            Format::Json => "JSON",
+            Format::Xml if is_html(syntax, text) => "HTML",
+            Format::Xml => "XML",
+        }
+    }
+}
+
+// Whether `syntax` is one of the markup languages --format xml re-indents
+pub fn is_markup(syntax: &SyntaxReference) -> bool {
+    matches!(syntax.name.as_str(), "XML" | "HTML")
+}
+
+fn is_html(syntax: &SyntaxReference, text: &str) -> bool {
+    syntax.name == "HTML" || (syntax.name != "XML" && xml::looks_like_html(text))
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use syntect::parsing::SyntaxReference;
+
+// Whether documents are rendered instead of shown as highlighted source
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderMode {
+    // Render the formats that have a renderer, when output goes to a terminal
+    Auto,
+    // Render the input as Markdown, whatever its syntax
+    Markdown,
+    // Render the input as a Jupyter notebook
+    Notebook,
+    // Render the input as a table of delimited values
+    Table,
+    // Always show the source
+    Never,
+}
+
+// A way of showing an input other than as its highlighted source
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    Markdown,
+    Notebook,
+    Table,
+}
+
+impl RenderMode {
+    // The renderer for an input detected as `syntax`, if it's to be rendered. Notebooks
+    // are JSON as far as syntax detection goes, and delimited values have no syntax, so
+    // they're told apart by their extension
+    pub fn renderer(self, syntax: &SyntaxReference, file_path: Option<&Path>) -> Option<Renderer> {
+        match self {
+            RenderMode::Auto if file_path.is_some_and(is_notebook) => Some(Renderer::Notebook),
+            RenderMode::Auto if file_path.is_some_and(is_table) => Some(Renderer::Table),
+            RenderMode::Auto => (syntax.name == "Markdown").then_some(Renderer::Markdown),
+            RenderMode::Markdown => Some(Renderer::Markdown),
+            RenderMode::Notebook => Some(Renderer::Notebook),
+            RenderMode::Table => Some(Renderer::Table),
+            RenderMode::Never => None,
+        }
+    }
+}
+
+fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+fn is_table(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ["csv", "tsv", "tab"]
+            .iter()
+            .any(|table| ext.eq_ignore_ascii_case(table))
+    })
+}
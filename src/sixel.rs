@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use image::imageops::FilterType;
+
+// Encode an image as sixel graphics, scaled to `width` pixels wide (keeping its aspect
+// ratio). Colors are reduced to a 6x6x6 color cube, which every sixel terminal's palette
+// has room for, and transparent pixels are left unpainted. None if it can't be decoded
+pub fn encode(data: &[u8], width: u32) -> Option<String> {
+    let image = image::load_from_memory(data).ok()?;
+    let image = if width < image.width() {
+        let height =
+            (u64::from(image.height()) * u64::from(width) / u64::from(image.width())).max(1) as u32;
+        image.resize_exact(width, height, FilterType::Triangle)
+    } else {
+        image
+    };
+    let image = image.to_rgba8();
+    let (width, height) = image.dimensions();
+
+    // The palette index of every pixel, or None for transparent ones
+    let indices: Vec<Option<u8>> = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            (a >= 128).then(|| cube_level(r) * 36 + cube_level(g) * 6 + cube_level(b))
+        })
+        .collect();
+
+    // The raster attributes give the size up front; P2 = 1 keeps unpainted pixels
+    // transparent
+    let mut sixel = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for index in indices.iter().flatten().collect::<BTreeSet<_>>() {
+        // Palette colors are given in percent
+        let percent = |level: u8| u32::from(level) * 100 / 5;
+        let _ = write!(
+            sixel,
+            "#{};2;{};{};{}",
+            index,
+            percent(index / 36),
+            percent(index / 6 % 6),
+            percent(index % 6)
+        );
+    }
+
+    // Each band of six rows is drawn once per color in it, going back to the start of the
+    // band ($) between colors and on to the next band (-) after the last one
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let pixel = |x: u32, y: u32| indices[(y * width + x) as usize];
+        let colors: BTreeSet<u8> = rows
+            .clone()
+            .flat_map(|y| (0..width).filter_map(move |x| pixel(x, y)))
+            .collect();
+        for (nth, &color) in colors.iter().enumerate() {
+            if nth > 0 {
+                sixel.push('$');
+            }
+            let _ = write!(sixel, "#{}", color);
+            let columns = (0..width).map(|x| {
+                rows.clone()
+                    .filter(|&y| pixel(x, y) == Some(color))
+                    .fold(0, |bits, y| bits | 1 << (y - band))
+            });
+            write_run_length(&mut sixel, columns);
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+    Some(sixel)
+}
+
+// The level (0 to 5) of a color channel in the 6x6x6 cube
+fn cube_level(channel: u8) -> u8 {
+    ((u16::from(channel) * 5 + 127) / 255) as u8
+}
+
+// Write the sixels for a row of columns (each a 6-bit column of pixels), with runs of the
+// same sixel written once with a repeat count
+fn write_run_length(sixel: &mut String, columns: impl Iterator<Item = u8>) {
+    let mut run: Option<(u8, usize)> = None;
+    let flush = |sixel: &mut String, (bits, count): (u8, usize)| {
+        let c = char::from(63 + bits);
+        if count > 3 {
+            let _ = write!(sixel, "!{}{}", count, c);
+        } else {
+            sixel.extend(std::iter::repeat_n(c, count));
+        }
+    };
+    for bits in columns {
+        run = match run {
+            Some((current, count)) if current == bits => Some((current, count + 1)),
+            Some(previous) => {
+                flush(sixel, previous);
+                Some((bits, 1))
+            }
+            None => Some((bits, 1)),
+        };
+    }
+    if let Some(last) = run {
+        flush(sixel, last);
+    }
+}
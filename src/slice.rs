@@ -0,0 +1,126 @@
+use std::io::{self, BufRead, Read};
+
+use crate::terminal;
+
+// The terminal columns --columns keeps of each line, counted from 1 with both ends included
+#[derive(Clone, Copy, Debug)]
+pub struct ColumnRange {
+    start: usize,
+    end: usize,
+}
+
+impl ColumnRange {
+    // Parse "N:M". Either end may be left out (":80" starts at the first column, "40:"
+    // runs to the end of the line), and "N" alone is that one column
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let column = |text: &str, default: usize| match text.trim() {
+            "" => Ok(default),
+            text => match text.parse::<usize>() {
+                Ok(0) => Err("columns start at 1".to_string()),
+                Ok(number) => Ok(number),
+                Err(_) => Err(format!("invalid column '{}'", text)),
+            },
+        };
+        let (start, end) = match value.split_once(':') {
+            Some((start, end)) => (column(start, 1)?, column(end, usize::MAX)?),
+            None => {
+                let column = column(value, 0)?;
+                (column, column)
+            }
+        };
+        if start == 0 || start > end {
+            return Err(format!("invalid column range '{}'", value));
+        }
+        Ok(ColumnRange { start, end })
+    }
+
+    // Cut `text`, a line possibly with color escapes in it, down to the characters that
+    // fall within the range. Every escape is kept, so the colors carry on as they were,
+    // and so is the line ending; a wide character only partly inside is left out
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut column = 0;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if c == '\x1b' {
+                let len = terminal::escape_len(rest);
+                out.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+            let width = terminal::char_width(c, column);
+            if matches!(c, '\n' | '\r')
+                || (column >= self.start - 1 && column.saturating_add(width) <= self.end)
+            {
+                out.push(c);
+            }
+            column += width;
+            rest = &rest[c.len_utf8()..];
+        }
+        out
+    }
+}
+
+// The window of bytes --bytes shows of each input: `len` bytes (or the rest) from `offset`
+#[derive(Clone, Copy, Debug)]
+pub struct ByteRange {
+    offset: u64,
+    len: Option<u64>,
+}
+
+impl ByteRange {
+    // Parse "OFFSET:LEN", where OFFSET counts from 0 and LEN may be left out to read to
+    // the end
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (offset, len) = value
+            .split_once(':')
+            .ok_or_else(|| format!("expected OFFSET:LEN, got '{}'", value))?;
+        let number = |text: &str| {
+            text.trim()
+                .parse::<u64>()
+                .map_err(|_| format!("invalid byte count '{}'", text))
+        };
+        let offset = match offset.trim() {
+            "" => 0,
+            offset => number(offset)?,
+        };
+        let len = match len.trim() {
+            "" => None,
+            len => Some(number(len)?),
+        };
+        Ok(ByteRange { offset, len })
+    }
+
+    // Narrow `reader`, an input read as UTF-8, down to the window. Either end is moved
+    // inwards when it falls inside a character, so the window is still valid text
+    pub fn apply(&self, mut reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+        io::copy(&mut (&mut reader).take(self.offset), &mut io::sink())?;
+        // Continuation bytes are the rest of a character that started before the window
+        if self.offset > 0 {
+            loop {
+                let block = reader.fill_buf()?;
+                let continuation = block
+                    .iter()
+                    .take_while(|&&byte| byte & 0xc0 == 0x80)
+                    .count();
+                let done = continuation < block.len() || block.is_empty();
+                reader.consume(continuation);
+                if done {
+                    break;
+                }
+            }
+        }
+        let Some(len) = self.len else {
+            return Ok(reader);
+        };
+        let mut data = Vec::new();
+        reader.take(len).read_to_end(&mut data)?;
+        // A character cut off by the end of the window is left out
+        if let Err(err) = std::str::from_utf8(&data)
+            && err.error_len().is_none()
+        {
+            data.truncate(err.valid_up_to());
+        }
+        Ok(Box::new(io::Cursor::new(data)))
+    }
+}
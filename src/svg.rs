@@ -0,0 +1,185 @@
+use std::fmt::Write as _;
+
+use syntect::highlighting::{Color, FontStyle, Style, Theme};
+
+// Layout settings for SVG export
+pub struct SvgOptions {
+    // CSS font-family list used for the code
+    pub font_family: String,
+    // Font size in pixels
+    pub font_size: f32,
+    // Space between the code and the edge of the image, in pixels
+    pub padding: f32,
+    // Background color; the theme's background when None
+    pub background: Option<Color>,
+    pub line_numbers: bool,
+}
+
+// Monospace fonts are roughly this wide relative to their size, which is used to size the
+// image since SVG has no way to ask for the text's extent
+const CHARACTER_WIDTH: f32 = 0.6;
+const LINE_HEIGHT: f32 = 1.4;
+// Tabs are expanded so that positions don't depend on the renderer's tab handling
+const TAB_WIDTH: usize = 4;
+
+// Render highlighted lines (each with its line number) into a standalone SVG document
+pub fn render_svg(
+    theme: &Theme,
+    lines: &[(usize, Vec<(Style, String)>)],
+    options: &SvgOptions,
+) -> String {
+    let background = options
+        .background
+        .or(theme.settings.background)
+        .unwrap_or(Color::WHITE);
+    let gutter = theme
+        .settings
+        .gutter_foreground
+        .or(theme.settings.foreground)
+        .unwrap_or(Color::BLACK);
+
+    // Expand tabs and strip line endings up front, since the image size depends on the text
+    let expanded: Vec<(usize, Vec<(Style, String)>)> = lines
+        .iter()
+        .map(|(number, ranges)| (*number, expand_line(ranges)))
+        .collect();
+    let gutter_columns = if options.line_numbers {
+        lines
+            .iter()
+            .map(|(n, _)| *n)
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len()
+            + 1
+    } else {
+        0
+    };
+    let columns = expanded
+        .iter()
+        .map(|(_, ranges)| ranges.iter().map(|(_, t)| t.chars().count()).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        + gutter_columns;
+
+    let char_width = options.font_size * CHARACTER_WIDTH;
+    let line_height = options.font_size * LINE_HEIGHT;
+    let width = columns as f32 * char_width + 2.0 * options.padding;
+    let height = expanded.len() as f32 * line_height + 2.0 * options.padding;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">",
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        color_attr(background)
+    );
+    let _ = writeln!(
+        svg,
+        "<g font-family=\"{}\" font-size=\"{}\" xml:space=\"preserve\">",
+        escape(&options.font_family),
+        options.font_size
+    );
+
+    for (row, (line_number, ranges)) in expanded.iter().enumerate() {
+        // Text is positioned by its baseline, placed one font size below the top of the line
+        let y = options.padding + row as f32 * line_height + options.font_size;
+        let _ = write!(svg, "<text x=\"{}\" y=\"{:.1}\">", options.padding, y);
+        if options.line_numbers {
+            let _ = write!(
+                svg,
+                "<tspan fill=\"{}\" fill-opacity=\"0.6\">{:>width$} </tspan>",
+                color_attr(gutter),
+                line_number,
+                width = gutter_columns - 1
+            );
+        }
+        for (style, text) in ranges {
+            if text.is_empty() {
+                continue;
+            }
+            let _ = write!(
+                svg,
+                "<tspan fill=\"{}\"{}>{}</tspan>",
+                color_attr(style.foreground),
+                font_attrs(style.font_style),
+                escape(text)
+            );
+        }
+        svg.push_str("</text>\n");
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+// Parse a background color given as "#rrggbb" or "rrggbb"
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let channel = |index: usize| {
+        hex.get(index..index + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color { r, g, b, a: 0xff }),
+        _ => Err(format!("'{}' is not a #rrggbb color", value)),
+    }
+}
+
+// Expand tabs to spaces and drop the line ending
+pub fn expand_line(ranges: &[(Style, String)]) -> Vec<(Style, String)> {
+    let mut column = 0;
+    ranges
+        .iter()
+        .map(|(style, text)| {
+            let mut expanded = String::new();
+            for c in text.chars() {
+                match c {
+                    '\n' | '\r' => {}
+                    '\t' => {
+                        let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                        expanded.push_str(&" ".repeat(spaces));
+                        column += spaces;
+                    }
+                    _ => {
+                        expanded.push(c);
+                        column += 1;
+                    }
+                }
+            }
+            (*style, expanded)
+        })
+        .collect()
+}
+
+// SVG attributes for bold/italic/underline text
+fn font_attrs(font_style: FontStyle) -> String {
+    let mut attrs = String::new();
+    if font_style.contains(FontStyle::BOLD) {
+        attrs.push_str(" font-weight=\"bold\"");
+    }
+    if font_style.contains(FontStyle::ITALIC) {
+        attrs.push_str(" font-style=\"italic\"");
+    }
+    if font_style.contains(FontStyle::UNDERLINE) {
+        attrs.push_str(" text-decoration=\"underline\"");
+    }
+    attrs
+}
+
+// A theme color as an SVG color attribute value
+fn color_attr(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+// Escape text for use in XML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use crate::terminal;
+
+const HEADER: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// Columns are never shrunk narrower than this to fit the terminal; what doesn't fit then is
+// cut off at the right instead
+const MIN_COLUMN_WIDTH: usize = 3;
+
+// The field delimiter given with --delimiter: a single ASCII character, or "tab" (or "\t")
+pub fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "'{}' isn't a single ASCII character (or \"tab\")",
+            value
+        )),
+    }
+}
+
+// The delimiter of an input without one given: tabs for .tsv files, commas for .csv ones,
+// and otherwise whichever of the two the first line has more of
+pub fn delimiter_for(file_path: Option<&Path>, text: &str) -> u8 {
+    let extension = file_path
+        .and_then(Path::extension)
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("tsv" | "tab") => b'\t',
+        Some("csv") => b',',
+        _ => {
+            let first = text.lines().next().unwrap_or_default();
+            if first.matches('\t').count() > first.matches(',').count() {
+                b'\t'
+            } else {
+                b','
+            }
+        }
+    }
+}
+
+// Render delimited text as a table: its columns lined up, the first row styled as the
+// header under a rule, and numeric columns (header included) aligned to the right. Tables
+// wider than `width` have their widest columns shrunk, with cut off cells ending in "…",
+// and columns that still don't fit are left out. None if `text` can't be parsed, so it's
+// shown as it is instead
+pub fn render(text: &str, delimiter: u8, width: usize) -> Option<String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let rows: Vec<Vec<String>> = reader
+        .records()
+        .map(|record| {
+            record.map(|record| {
+                record
+                    .iter()
+                    // A cell is kept on one line of the table
+                    .map(|cell| cell.replace(['\r', '\n', '\t'], " "))
+                    .collect()
+            })
+        })
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return Some(String::new());
+    }
+
+    let mut widths = vec![0; columns];
+    for row in &rows {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(terminal::display_width(cell));
+        }
+    }
+    let numeric: Vec<bool> = (0..columns)
+        .map(|column| {
+            let mut cells = rows
+                .iter()
+                .skip(1)
+                .filter_map(|row| row.get(column))
+                .filter(|cell| !cell.trim().is_empty())
+                .peekable();
+            cells.peek().is_some() && cells.all(|cell| cell.trim().parse::<f64>().is_ok())
+        })
+        .collect();
+
+    let shown = fit(&mut widths, width);
+    let cut = shown < columns;
+    let separator = format!("{} │ {}", DIM, RESET);
+
+    let mut out = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        let style = if index == 0 { HEADER } else { "" };
+        let cells: Vec<String> = (0..shown)
+            .map(|column| {
+                let cell = row.get(column).map_or("", String::as_str);
+                let cell = truncate(cell, widths[column]);
+                let padding = " ".repeat(widths[column] - terminal::display_width(&cell));
+                if numeric[column] {
+                    format!("{}{}{}{}", padding, style, cell, RESET)
+                } else {
+                    format!("{}{}{}{}", style, cell, RESET, padding)
+                }
+            })
+            .collect();
+        let mut line = cells.join(&separator);
+        if cut {
+            line.push_str(&format!("{} │ …{}", DIM, RESET));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+        if index == 0 {
+            let mut rule = widths[..shown]
+                .iter()
+                .map(|&width| "─".repeat(width))
+                .collect::<Vec<_>>()
+                .join("─┼─");
+            if cut {
+                rule.push_str("─┼──");
+            }
+            out.push_str(&format!("{}{}{}\n", DIM, rule, RESET));
+        }
+    }
+    Some(out)
+}
+
+// Shrink `widths` until the columns, with the separators between them, fit in `width`,
+// taking one column off the widest one each time. Returns how many columns are shown, which
+// is fewer than all of them when even the narrowest columns don't fit
+fn fit(widths: &mut [usize], width: usize) -> usize {
+    // Room taken up by the separators between `columns` columns, and the marker for the
+    // columns that were left out
+    let separators = |columns: usize, cut: bool| 3 * (columns - 1) + if cut { 4 } else { 0 };
+    let total = |widths: &[usize]| widths.iter().sum::<usize>();
+    let mut shown = widths.len();
+    loop {
+        let cut = shown < widths.len();
+        if total(&widths[..shown]) + separators(shown, cut) <= width {
+            return shown;
+        }
+        let widest = (0..shown).max_by_key(|&column| widths[column]).unwrap_or(0);
+        if widths[widest] > MIN_COLUMN_WIDTH {
+            widths[widest] -= 1;
+        } else if shown > 1 {
+            shown -= 1;
+        } else {
+            return shown;
+        }
+    }
+}
+
+// `cell` cut down to `width` columns, ending in "…" when it had to be cut
+fn truncate(cell: &str, width: usize) -> String {
+    if terminal::display_width(cell) <= width {
+        return cell.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in cell.chars() {
+        let c_width = terminal::char_width(c, used);
+        if used + c_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += c_width;
+    }
+    truncated.push('…');
+    truncated
+}
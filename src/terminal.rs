@@ -0,0 +1,575 @@
+use std::env;
+use std::fmt::Write as _;
+#[cfg(unix)]
+use std::fs::{File, OpenOptions};
+#[cfg(unix)]
+use std::io::Read;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+#[cfg(unix)]
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use syntect::highlighting::{Color, Style};
+use unicode_width::UnicodeWidthChar;
+
+// How many colors the terminal can display
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorDepth {
+    // Guess from COLORTERM and TERM
+    Auto,
+    // 24-bit RGB colors
+    #[value(name = "truecolor")]
+    TrueColor,
+    // The xterm 256-color palette
+    #[value(name = "256")]
+    Ansi256,
+    // The 16 basic ANSI colors
+    #[value(name = "16")]
+    Ansi16,
+}
+
+impl ColorDepth {
+    // Replace Auto with the depth detected from the environment
+    pub fn resolve(self) -> Self {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+        match env::var("TERM") {
+            Ok(term) if term.contains("256color") => ColorDepth::Ansi256,
+            Ok(term) if term == "linux" || term.starts_with("vt") || term.contains("16color") => {
+                ColorDepth::Ansi16
+            }
+            // Modern Windows consoles handle 24-bit color but don't set TERM
+            Err(_) if cfg!(windows) => ColorDepth::TrueColor,
+            // Anything else that understands escapes almost certainly has the 256-color palette
+            _ => ColorDepth::Ansi256,
+        }
+    }
+}
+
+// Like syntect's `as_24_bit_terminal_escaped`, but with colors reduced to `depth`
+pub fn as_terminal_escaped(
+    ranges: &[(Style, &str)],
+    background: bool,
+    depth: ColorDepth,
+) -> String {
+    let mut out = String::new();
+    for (style, text) in ranges {
+        if background {
+            out.push_str(&background_escape(style.background, depth));
+        }
+        out.push_str(&foreground_escape(style.foreground, depth));
+        out.push_str(text);
+    }
+    out
+}
+
+// Escape sequence selecting `color` as the foreground color
+pub fn foreground_escape(color: Color, depth: ColorDepth) -> String {
+    color_escape(color, depth, 38, 30)
+}
+
+// Escape sequence selecting `color` as the background color
+pub fn background_escape(color: Color, depth: ColorDepth) -> String {
+    color_escape(color, depth, 48, 40)
+}
+
+// Build the SGR sequence for `color`: `extended` is 38/48 for the 256/RGB forms and `basic`
+// is 30/40 for the 16-color form
+fn color_escape(color: Color, depth: ColorDepth, extended: u8, basic: u8) -> String {
+    let mut escape = String::new();
+    match depth {
+        ColorDepth::TrueColor | ColorDepth::Auto => {
+            let _ = write!(
+                escape,
+                "\x1b[{};2;{};{};{}m",
+                extended, color.r, color.g, color.b
+            );
+        }
+        ColorDepth::Ansi256 => {
+            let _ = write!(escape, "\x1b[{};5;{}m", extended, to_ansi256(color));
+        }
+        ColorDepth::Ansi16 => {
+            let index = to_ansi16(color);
+            // Bright colors (8-15) use the 90+/100+ codes
+            let code = if index < 8 {
+                basic + index
+            } else {
+                basic + 60 + index - 8
+            };
+            let _ = write!(escape, "\x1b[{}m", code);
+        }
+    }
+    escape
+}
+
+// Nearest color in the xterm 256-color palette: either the 6x6x6 color cube or the
+// 24-step grayscale ramp, whichever is closer
+pub fn to_ansi256(color: Color) -> u8 {
+    // Levels used by each axis of the color cube
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |value: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (
+        nearest_level(color.r),
+        nearest_level(color.g),
+        nearest_level(color.b),
+    );
+    let cube_index = 16 + 36 * r + 6 * g + b;
+    let cube_color = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    // Grayscale ramp: 232..=255 cover 8, 18, ..., 238
+    let average = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_value = 8 + gray_step * 10;
+    let gray_index = 232 + gray_step as usize;
+
+    let target = (color.r, color.g, color.b);
+    if distance(target, (gray_value, gray_value, gray_value)) < distance(target, cube_color) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+// Nearest of the 16 basic ANSI colors (as rendered by xterm), as an index 0-15
+pub fn to_ansi16(color: Color) -> u8 {
+    const PALETTE: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    let target = (color.r, color.g, color.b);
+    (0..16u8)
+        .min_by_key(|&i| distance(target, PALETTE[i as usize]))
+        .unwrap_or(7)
+}
+
+// Squared distance between two colors, weighted roughly by how sensitive the eye is to
+// each channel
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    (2 * dr * dr + 4 * dg * dg + 3 * db * db) as u32
+}
+
+// When to emit color escape sequences
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    // Only when writing to a terminal and NO_COLOR isn't set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    // Whether output should be colored, given whether it's going to a terminal
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // https://no-color.org: any non-empty value disables color
+                let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                !no_color && is_terminal
+            }
+        }
+    }
+}
+
+// When to soft-wrap lines that are wider than the terminal
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WrapMode {
+    // Wrap when writing to a terminal
+    Auto,
+    // Always wrap, breaking lines between any two characters
+    Character,
+    // Never wrap, leaving long lines to the terminal or pager
+    Never,
+}
+
+// A width given with --terminal-width: a number of columns, or an adjustment (+N or -N) to
+// the width of the actual terminal
+#[derive(Clone, Copy)]
+pub enum TerminalWidth {
+    Columns(usize),
+    Relative(isize),
+}
+
+impl TerminalWidth {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let invalid = || format!("invalid width '{}'", value);
+        if value.starts_with(['+', '-']) {
+            value
+                .parse::<isize>()
+                .map(TerminalWidth::Relative)
+                .map_err(|_| invalid())
+        } else {
+            match value.parse::<usize>() {
+                Ok(0) | Err(_) => Err(invalid()),
+                Ok(columns) => Ok(TerminalWidth::Columns(columns)),
+            }
+        }
+    }
+}
+
+// Width of the output in columns: the --terminal-width override if there is one, otherwise
+// the terminal's width, or 80 when there's no terminal to ask
+pub fn width(choice: Option<TerminalWidth>) -> usize {
+    #[cfg(not(target_arch = "wasm32"))]
+    let detected = || crossterm::terminal::size().map_or(80, |(columns, _)| columns as usize);
+    // wasm builds have no terminal to ask
+    #[cfg(target_arch = "wasm32")]
+    let detected = || 80_usize;
+    match choice {
+        Some(TerminalWidth::Columns(columns)) => columns,
+        Some(TerminalWidth::Relative(delta)) => detected().saturating_add_signed(delta).max(1),
+        None => detected(),
+    }
+}
+
+// Whether the console shows escapes as colors rather than as text. Windows consoles only do
+// once virtual terminal processing is turned on, which this asks for; the console in
+// older versions of Windows has no such mode
+#[cfg(windows)]
+pub fn enable_ansi() -> bool {
+    crossterm::ansi_support::supports_ansi()
+}
+
+// Terminals everywhere else always do
+#[cfg(not(windows))]
+pub fn enable_ansi() -> bool {
+    true
+}
+
+// Send `query` to the terminal and return everything it answers, or None when there's no
+// terminal to ask. A primary device attributes request (DA1) goes after the query: every
+// terminal answers that one, and in order, so a reply to it without one to `query` before
+// it means `query` isn't supported, with no need to wait for a timeout. Terminals that
+// don't answer at all are still given up on after a short wait. Each byte is only read
+// once poll says it's there, so nothing is left waiting on the terminal afterwards to
+// take the keys typed next
+#[cfg(unix)]
+pub fn query(query: &str) -> Option<String> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    // Raw mode keeps the reply from being echoed, and lets it be read without a newline
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let reply = write!(tty, "{}\x1b[c", query)
+        .and_then(|_| tty.flush())
+        .ok()
+        .and_then(|_| read_reply(&mut tty, Instant::now() + Duration::from_millis(200)));
+    let _ = crossterm::terminal::disable_raw_mode();
+    Some(String::from_utf8_lossy(&reply?).into_owned())
+}
+
+// There's no /dev/tty to ask anywhere else
+#[cfg(not(unix))]
+pub fn query(_query: &str) -> Option<String> {
+    None
+}
+
+// Read from `tty` up to the end of the DA1 reply, or None when it isn't all there by
+// `deadline`
+#[cfg(unix)]
+fn read_reply(tty: &mut File, deadline: Instant) -> Option<Vec<u8>> {
+    let mut reply = Vec::new();
+    let mut byte = [0];
+    while !ends_with_device_attributes(&reply) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let mut poll_fd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: poll_fd is a single valid pollfd that outlives the call
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, left.as_millis() as libc::c_int) };
+        if ready == 0
+            || (ready < 0 && io::Error::last_os_error().kind() != io::ErrorKind::Interrupted)
+        {
+            return None;
+        }
+        if ready > 0 {
+            if tty.read_exact(&mut byte).is_err() {
+                break;
+            }
+            reply.push(byte[0]);
+        }
+    }
+    Some(reply)
+}
+
+// Whether `reply` ends with a DA1 reply, which looks like ESC [ ? 6 2 ; 4 c
+#[cfg(unix)]
+fn ends_with_device_attributes(reply: &[u8]) -> bool {
+    reply.ends_with(b"c")
+        && reply
+            .windows(3)
+            .rposition(|window| window == b"\x1b[?")
+            .is_some_and(|start| {
+                reply[start + 3..reply.len() - 1]
+                    .iter()
+                    .all(|&byte| byte.is_ascii_digit() || byte == b';')
+            })
+}
+
+// Whether the terminal can show sixel graphics, which it says by listing attribute 4 in
+// its DA1 reply
+pub fn supports_sixel() -> bool {
+    query("").is_some_and(|reply| {
+        let Some(start) = reply.rfind("\x1b[?") else {
+            return false;
+        };
+        reply[start + 3..]
+            .trim_end_matches('c')
+            .split(';')
+            .any(|attribute| attribute == "4")
+    })
+}
+
+// Whether the terminal's background is light, going by the color it reports for it when
+// asked with OSC 11, or None when it doesn't answer. The reply looks like
+// ESC ] 11 ; rgb:RRRR/GGGG/BBBB followed by BEL or ST, with 1 to 4 hex digits a component
+pub fn background_is_light() -> Option<bool> {
+    let reply = query("\x1b]11;?\x07")?;
+    let start = reply.find("]11;rgb:")? + "]11;rgb:".len();
+    let rest = &reply[start..];
+    let rgb = &rest[..rest.find(['\x07', '\x1b'])?];
+    let components = rgb
+        .split('/')
+        .map(|hex| {
+            let value = u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() <= 4)?;
+            Some(value as f64 / ((1 << (4 * hex.len())) - 1) as f64)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let [r, g, b] = components[..] else {
+        return None;
+    };
+    // Relative luminance, as the eye weighs the components, past the middle is light
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5)
+}
+
+// A writer that removes ANSI escape sequences on their way through when `strip` is set, so
+// decorations written with colors come out as plain text. It tracks partial sequences, so
+// escapes split across writes are still removed. Hyperlinks (OSC 8) can be let through,
+// since they work without colors
+pub struct AnsiFilter<W: Write> {
+    inner: W,
+    strip: bool,
+    keep_hyperlinks: bool,
+    state: EscapeState,
+    // The OSC sequence read so far, held back until its end shows whether it's a link
+    osc: Vec<u8>,
+}
+
+// Where the filter is within an escape sequence
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    Text,
+    // Just saw ESC
+    Escape,
+    // Inside "ESC [ ..."; ends with a byte in 0x40..=0x7e
+    Csi,
+    // Inside "ESC ] ..."; ends with BEL or ESC \
+    Osc,
+    // Saw ESC inside an OSC sequence, which may be the start of its ESC \ terminator
+    OscEscape,
+}
+
+impl<W: Write> AnsiFilter<W> {
+    pub fn new(inner: W, strip: bool) -> Self {
+        AnsiFilter {
+            inner,
+            strip,
+            keep_hyperlinks: false,
+            state: EscapeState::Text,
+            osc: Vec::new(),
+        }
+    }
+
+    // Let hyperlinks through even when stripping the other escapes
+    pub fn keep_hyperlinks(mut self, keep: bool) -> Self {
+        self.keep_hyperlinks = keep;
+        self
+    }
+
+    // The writer the filtered output goes to
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: Write> Write for AnsiFilter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.strip {
+            return self.inner.write(buf);
+        }
+        let mut text = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            if matches!(self.state, EscapeState::Osc | EscapeState::OscEscape) {
+                self.osc.push(byte);
+            }
+            self.state = match (self.state, byte) {
+                (EscapeState::Text, 0x1b) => EscapeState::Escape,
+                (EscapeState::Text, _) => {
+                    text.push(byte);
+                    EscapeState::Text
+                }
+                (EscapeState::Escape, b'[') => EscapeState::Csi,
+                (EscapeState::Escape, b']') => {
+                    self.osc.extend_from_slice(b"\x1b]");
+                    EscapeState::Osc
+                }
+                // Two-byte sequences such as "ESC c"
+                (EscapeState::Escape, _) => EscapeState::Text,
+                (EscapeState::Csi, 0x40..=0x7e) => EscapeState::Text,
+                (EscapeState::Csi, _) => EscapeState::Csi,
+                (EscapeState::Osc, 0x07) | (EscapeState::OscEscape, b'\\') => {
+                    if self.keep_hyperlinks && self.osc.starts_with(b"\x1b]8;") {
+                        text.extend_from_slice(&self.osc);
+                    }
+                    self.osc.clear();
+                    EscapeState::Text
+                }
+                (EscapeState::Osc, 0x1b) => EscapeState::OscEscape,
+                (EscapeState::Osc, _) => EscapeState::Osc,
+                (EscapeState::OscEscape, _) => EscapeState::Osc,
+            };
+        }
+        self.inner.write_all(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Length in bytes of the escape sequence starting at the beginning of `s` (which starts with ESC)
+pub fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        // CSI sequences (colors, cursor movement) end with a byte in 0x40..=0x7e
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |end| end + 3),
+        // OSC sequences (e.g. hyperlinks) end with BEL or ESC \
+        Some(b']') => {
+            let mut index = 2;
+            while index < bytes.len() {
+                if bytes[index] == 0x07 {
+                    return index + 1;
+                }
+                if bytes[index] == 0x1b && bytes.get(index + 1) == Some(&b'\\') {
+                    return index + 2;
+                }
+                index += 1;
+            }
+            bytes.len()
+        }
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+// Remove escape sequences, leaving only the visible text (e.g. for searching or measuring)
+pub fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            rest = &rest[escape_len(rest)..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+// Width of a character in terminal columns, where a tab at `column` reaches the next
+// multiple of 8
+pub fn char_width(c: char, column: usize) -> usize {
+    if c == '\t' {
+        8 - column % 8
+    } else {
+        UnicodeWidthChar::width(c).unwrap_or(0)
+    }
+}
+
+// Width of `text` in terminal columns, ignoring escape sequences
+pub fn display_width(text: &str) -> usize {
+    strip_ansi(text)
+        .chars()
+        .fold(0, |column, c| column + char_width(c, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 0xff }
+    }
+
+    #[test]
+    fn to_ansi256_picks_the_color_cube_or_the_gray_ramp() {
+        assert_eq!(to_ansi256(rgb(255, 0, 0)), 196);
+        assert_eq!(to_ansi256(rgb(0, 95, 0)), 22);
+        assert_eq!(to_ansi256(rgb(0, 0, 0)), 16);
+        assert_eq!(to_ansi256(rgb(128, 128, 128)), 244);
+        assert_eq!(to_ansi256(rgb(238, 238, 238)), 255);
+    }
+
+    #[test]
+    fn to_ansi16_picks_the_nearest_basic_color() {
+        assert_eq!(to_ansi16(rgb(0, 0, 0)), 0);
+        assert_eq!(to_ansi16(rgb(200, 10, 10)), 1);
+        assert_eq!(to_ansi16(rgb(10, 250, 10)), 10);
+        assert_eq!(to_ansi16(rgb(250, 250, 250)), 15);
+    }
+
+    #[test]
+    fn background_escape_follows_the_depth() {
+        let color = rgb(255, 0, 0);
+        assert_eq!(
+            background_escape(color, ColorDepth::TrueColor),
+            "\x1b[48;2;255;0;0m"
+        );
+        assert_eq!(
+            background_escape(color, ColorDepth::Ansi256),
+            "\x1b[48;5;196m"
+        );
+        assert_eq!(background_escape(color, ColorDepth::Ansi16), "\x1b[101m");
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use syntect::highlighting::{Color, FontStyle};
+
+use crate::highlight::Span;
+
+// A line of --tokens output: a JSON object with the file (null for stdin), the line's
+// number and the spans it's highlighted with, their offsets counted in bytes from the
+// start of the line
+#[derive(Serialize)]
+struct LineRecord<'a> {
+    file: Option<String>,
+    line: usize,
+    spans: Vec<SpanRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct SpanRecord<'a> {
+    start: usize,
+    end: usize,
+    fg: String,
+    bg: String,
+    font_style: Vec<&'static str>,
+    scope: &'a str,
+}
+
+// Write the --tokens line for line `line_number` of `file`, highlighted as `spans`
+pub fn write_line_record(
+    handle: &mut dyn Write,
+    file: Option<&Path>,
+    line_number: usize,
+    spans: &[Span],
+) -> io::Result<()> {
+    let record = LineRecord {
+        file: file.map(|path| path.display().to_string()),
+        line: line_number,
+        spans: spans.iter().map(span_record).collect(),
+    };
+    serde_json::to_writer(&mut *handle, &record)?;
+    writeln!(handle)
+}
+
+fn span_record(span: &Span) -> SpanRecord<'_> {
+    let font_style = [
+        (FontStyle::BOLD, "bold"),
+        (FontStyle::ITALIC, "italic"),
+        (FontStyle::UNDERLINE, "underline"),
+    ]
+    .into_iter()
+    .filter(|&(style, _)| span.font_style.contains(style))
+    .map(|(_, name)| name)
+    .collect();
+    SpanRecord {
+        start: span.start,
+        end: span.end,
+        fg: hex(span.foreground),
+        bg: hex(span.background),
+        font_style,
+        scope: &span.scope,
+    }
+}
+
+// A color as "#rrggbb", with the alpha added on when it isn't opaque
+fn hex(color: Color) -> String {
+    let rgb = format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+    if color.a == 0xff {
+        rgb
+    } else {
+        format!("{}{:02x}", rgb, color.a)
+    }
+}
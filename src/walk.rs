@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
+
+use crate::error::CathError;
+use crate::input;
+
+// How directories are walked for --recursive
+pub struct WalkOptions {
+    pub max_depth: Option<usize>,
+    // Include hidden files and directories (whose names start with a dot)
+    pub hidden: bool,
+    // Include files that .gitignore, .ignore and the like exclude
+    pub no_ignore: bool,
+    // Globs for the files to print (all of them when empty), and for files and
+    // directories to leave out, e.g. "*.rs" and "target/*"
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+// Replace the directories among `paths` with the text files inside them, for --recursive.
+// Files are listed in name order, directory by directory, and binary files are left out
+// since a hex dump of every one of them would bury the text. Like git, hidden files and
+// anything ignored (build output, node_modules, ...) are skipped unless asked for. Other
+// paths are kept as they are, including files given explicitly, binary or not. Directories
+// that can't be read are handed to `report`, and the walk goes on without them
+pub fn expand_directories(
+    paths: &[PathBuf],
+    options: &WalkOptions,
+    report: &mut dyn FnMut(CathError),
+) -> Result<Vec<PathBuf>, CathError> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            expanded.push(path.clone());
+            continue;
+        }
+        let walker = WalkBuilder::new(path)
+            .overrides(filters(path, options)?)
+            .max_depth(options.max_depth)
+            .hidden(!options.hidden)
+            .standard_filters(!options.no_ignore)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            // Git's own files are never wanted, even with --hidden
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+        for entry in walker {
+            match entry {
+                Ok(entry)
+                    if entry.file_type().is_some_and(|kind| kind.is_file())
+                        && is_text_file(entry.path()) =>
+                {
+                    expanded.push(entry.into_path());
+                }
+                Ok(_) => {}
+                Err(source) => report(CathError::Walk { glob: None, source }),
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+// The --include and --exclude globs for walking `root`, which they're relative to
+fn filters(root: &Path, options: &WalkOptions) -> Result<Override, CathError> {
+    let mut builder = OverrideBuilder::new(root);
+    let globs = options
+        .include
+        .iter()
+        .map(String::from)
+        .chain(options.exclude.iter().map(|glob| format!("!{}", glob)));
+    for glob in globs {
+        builder.add(&glob).map_err(|source| CathError::Walk {
+            glob: Some(glob.trim_start_matches('!').to_string()),
+            source,
+        })?;
+    }
+    builder
+        .build()
+        .map_err(|source| CathError::Walk { glob: None, source })
+}
+
+// Whether the file at `path` looks like text, judging by its first block
+fn is_text_file(path: &Path) -> bool {
+    File::open(path)
+        .and_then(|file| input::is_binary(&mut BufReader::new(file), false))
+        .is_ok_and(|binary| !binary)
+}
@@ -0,0 +1,20 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{DEFAULT_THEME, render_html};
+
+// Highlight `code` as an HTML <pre> block with inline styles, for previews in the browser.
+// `lang` is a syntax name or file extension, detected from the code when it's undefined,
+// and `theme` the name of a color theme, the default one when it's undefined
+#[wasm_bindgen]
+pub fn highlight_to_html(
+    code: &str,
+    lang: Option<String>,
+    theme: Option<String>,
+) -> Result<String, JsError> {
+    render_html(
+        code,
+        lang.as_deref(),
+        theme.as_deref().unwrap_or(DEFAULT_THEME),
+    )
+    .map_err(|err| JsError::new(&err.to_string()))
+}
@@ -0,0 +1,77 @@
+// Watching goes through the file system notifications of the platform, which wasm builds
+// don't have
+use std::io;
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{collections::HashSet, path::Path, sync::mpsc, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+use notify::{EventKind, RecursiveMode, Watcher};
+
+// Editors often save with several writes (or write-then-rename) in quick succession, so
+// events arriving within this window are collapsed into a single re-render
+#[cfg(not(target_arch = "wasm32"))]
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+// Call `render` once, then again every time one of `paths` changes. This never returns
+// unless watching or rendering fails
+#[cfg(not(target_arch = "wasm32"))]
+pub fn watch(paths: &[PathBuf], mut render: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+
+    // Watch the parent directories rather than the files themselves, so a file that is
+    // replaced by a rename (as many editors do) keeps being watched
+    let targets: HashSet<PathBuf> = paths.iter().map(|path| absolute(path)).collect();
+    let dirs: HashSet<PathBuf> = targets
+        .iter()
+        .filter_map(|path| path.parent().map(Path::to_path_buf))
+        .collect();
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+    }
+
+    render()?;
+    loop {
+        let event = receiver
+            .recv()
+            .map_err(io::Error::other)?
+            .map_err(io::Error::other)?;
+        if !is_relevant(&event, &targets) {
+            continue;
+        }
+        // Swallow the rest of a burst of events before re-rendering
+        while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+        render()?;
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn watch(_paths: &[PathBuf], _render: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "watching files isn't supported in this build",
+    ))
+}
+
+// Whether `event` modified one of the watched files
+#[cfg(not(target_arch = "wasm32"))]
+fn is_relevant(event: &notify::Event, targets: &HashSet<PathBuf>) -> bool {
+    let changes_content = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    changes_content
+        && event
+            .paths
+            .iter()
+            .any(|path| targets.contains(&absolute(path)))
+}
+
+// Resolve `path` to an absolute one so event paths can be compared against it
+#[cfg(not(target_arch = "wasm32"))]
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,190 @@
+const INDENT: &str = "  ";
+
+// HTML elements that never have content or a closing tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// HTML elements whose content isn't markup, so it's only shifted to where the element is
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+// HTML elements where whitespace matters, which are kept exactly as they are
+const PREFORMATTED_ELEMENTS: [&str; 2] = ["pre", "textarea"];
+
+// The pieces an XML or HTML document is split into
+enum Token<'a> {
+    // A start tag, with the element's name
+    Open(&'a str, String),
+    // An end tag, with the element's name
+    Close(&'a str, String),
+    // Anything else in angle brackets (empty-element tags, comments, processing instructions,
+    // CDATA sections and doctypes), and preformatted elements
+    Other(&'a str),
+    Text(&'a str),
+    // The content of an element that's kept as it is
+    Raw(&'a str),
+}
+
+// Whether `text`, the start of a document, looks like HTML rather than XML
+pub fn looks_like_html(text: &str) -> bool {
+    let head = text
+        .trim_start()
+        .get(..100)
+        .unwrap_or(text)
+        .to_ascii_lowercase();
+    head.starts_with("<!doctype html") || head.contains("<html")
+}
+
+// Re-indent an XML (or, with `html` set, HTML) document, putting every tag on a line of
+// its own indented by how deep it's nested. Elements that hold nothing but a bit of text
+// stay on one line, and the text around tags is trimmed
+pub fn pretty(text: &str, html: bool) -> String {
+    let tokens = tokenize(text, html);
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut depth = 0;
+    let push_line = |out: &mut String, depth: usize, line: &str| {
+        out.push_str(&INDENT.repeat(depth));
+        out.push_str(line);
+        out.push('\n');
+    };
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        index += 1;
+        match token {
+            Token::Open(tag, name) => {
+                // The end tag of this element, if it's the token at `at`
+                let end_tag = |at: usize| match tokens.get(at) {
+                    Some(Token::Close(close, close_name)) if close_name == name => Some(*close),
+                    _ => None,
+                };
+                if let Some(Token::Text(text)) = tokens.get(index)
+                    && !text.contains('\n')
+                    && let Some(close) = end_tag(index + 1)
+                {
+                    push_line(&mut out, depth, &format!("{}{}{}", tag, text.trim(), close));
+                    index += 2;
+                } else if let Some(close) = end_tag(index) {
+                    push_line(&mut out, depth, &format!("{}{}", tag, close));
+                    index += 1;
+                } else {
+                    push_line(&mut out, depth, tag);
+                    if !(html && VOID_ELEMENTS.contains(&name.as_str())) {
+                        depth += 1;
+                    }
+                }
+            }
+            Token::Close(tag, _) => {
+                depth = depth.saturating_sub(1);
+                push_line(&mut out, depth, tag);
+            }
+            Token::Other(tag) => push_line(&mut out, depth, tag),
+            Token::Text(text) => {
+                for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    push_line(&mut out, depth, line);
+                }
+            }
+            // Raw content keeps its own indentation, shifted to where the element is
+            Token::Raw(text) => {
+                let lines: Vec<&str> = text
+                    .lines()
+                    .map(str::trim_end)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                let common = lines
+                    .iter()
+                    .map(|line| line.len() - line.trim_start().len())
+                    .min()
+                    .unwrap_or(0);
+                for line in lines {
+                    push_line(&mut out, depth, &line[common..]);
+                }
+            }
+        }
+    }
+    out
+}
+
+// Split a document into tags and the text between them
+fn tokenize(text: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        let start = text.len() - rest.len();
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        rest = &rest[end..];
+        if tag.starts_with("<!") || tag.starts_with("<?") || tag.ends_with("/>") {
+            tokens.push(Token::Other(tag));
+        } else if let Some(name) = tag.strip_prefix("</") {
+            tokens.push(Token::Close(tag, element_name(name, html)));
+        } else {
+            let name = element_name(&tag[1..], html);
+            if html && PREFORMATTED_ELEMENTS.contains(&name.as_str()) {
+                let close = content_end(rest, &name);
+                let element_end = close + tag_end(&rest[close..]);
+                tokens.push(Token::Other(&text[start..start + end + element_end]));
+                rest = &rest[element_end..];
+            } else if html && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let end = content_end(rest, &name);
+                tokens.push(Token::Open(tag, name));
+                tokens.push(Token::Raw(&rest[..end]));
+                rest = &rest[end..];
+            } else {
+                tokens.push(Token::Open(tag, name));
+            }
+        }
+    }
+    tokens
+}
+
+// Where the content of a raw text or preformatted element, which is everything up to its
+// end tag whatever it holds, ends in `rest`
+fn content_end(rest: &str, name: &str) -> usize {
+    rest.to_ascii_lowercase()
+        .find(&format!("</{}", name))
+        .unwrap_or(rest.len())
+}
+
+// The length of the tag, comment, CDATA section or other markup `rest` starts with, up to
+// and including its closing ">" (quoted attribute values can contain one). The rest of the
+// document when it never closes
+fn tag_end(rest: &str) -> usize {
+    for (open, close) in [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")] {
+        if let Some(inside) = rest.strip_prefix(open) {
+            return inside
+                .find(close)
+                .map_or(rest.len(), |end| open.len() + end + close.len());
+        }
+    }
+    let mut quote = None;
+    for (index, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '>') => return index + 1,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+// The name of the element a tag (without its "<" or "</") is for. HTML names are matched
+// up ignoring case
+fn element_name(tag: &str, html: bool) -> String {
+    let name: String = tag
+        .chars()
+        .take_while(|&c| !c.is_whitespace() && c != '/' && c != '>')
+        .collect();
+    if html {
+        name.to_ascii_lowercase()
+    } else {
+        name
+    }
+}